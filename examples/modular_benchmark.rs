@@ -0,0 +1,55 @@
+extern crate ellipticcurve;
+extern crate num_bigint;
+use ellipticcurve::bigint::PowerModulo;
+use ellipticcurve::modular::{BarrettContext, MontgomeryContext, Reduce};
+use ellipticcurve::u256::FieldElement;
+use num_bigint::BigInt;
+use std::time::Instant;
+
+fn bench<F: FnMut() -> BigInt>(name: &str, rounds: usize, mut f: F) -> BigInt {
+    let start = Instant::now();
+    let mut r = BigInt::from(0);
+    for _ in 0..rounds {
+        r = f();
+    }
+    let elapsed = start.elapsed();
+    println!("{:<24} {:>10.1} ns/op", name, elapsed.as_nanos() as f64 / rounds as f64);
+    r
+}
+
+/// cargo run --release --example modular_benchmark
+fn main() {
+    // secp256k1 field prime
+    let p = BigInt::parse_bytes(b"FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F", 16).unwrap();
+    let a = BigInt::parse_bytes(b"79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798", 16).unwrap();
+    let b = BigInt::parse_bytes(b"483ADA7726A3C4655DA4FBFC0E1108A8FD17B448A68554199C47D08FFB10D4B8", 16).unwrap();
+    let barrett = BarrettContext::new(&p);
+    let montgomery = MontgomeryContext::new(&p);
+    let am = montgomery.to_montgomery(&a);
+    let bm = montgomery.to_montgomery(&b);
+
+    println!("multiplication modulo the secp256k1 prime");
+    let r1 = bench("mod_floor", 100_000, || p.mul(&a, &b));
+    let r2 = bench("barrett", 100_000, || barrett.mul(&a, &b));
+    let r3 = bench("montgomery (in domain)", 100_000, || montgomery.mul_montgomery(&am, &bm));
+    let (af, bf) = (FieldElement::from_bigint(&a), FieldElement::from_bigint(&b));
+    let r4 = bench("fixed-width field", 100_000, || af.mul(&bf).to_bigint());
+    assert_eq!(r1, r2);
+    assert_eq!(r1, montgomery.from_montgomery(&r3));
+    assert_eq!(r1, r4);
+
+    println!("exponentiation a^(p-2) modulo the secp256k1 prime");
+    let e = &p - BigInt::from(2);
+    let r1 = bench("square and multiply", 100, || {
+        let mut r = BigInt::from(1);
+        for i in (0..e.bits()).rev() {
+            r = (&r * &r) % &p;
+            if (&e >> i) % 2 == BigInt::from(1) {
+                r = (&r * &a) % &p;
+            }
+        }
+        r
+    });
+    let r2 = bench("power_modulo", 100, || a.power_modulo(&e, &p));
+    assert_eq!(r1, r2);
+}
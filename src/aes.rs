@@ -0,0 +1,138 @@
+/// AES S-box, FIPS 197 5.1.1
+const SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+/// x * {02} in GF(2^8)
+fn xtime(x: u8) -> u8 {
+    (x << 1) ^ if x & 0x80 != 0 { 0x1b } else { 0 }
+}
+
+/// AES block cipher, encryption direction only (enough for CTR mode)
+#[derive(Debug, Clone)]
+pub struct Aes {
+    round_keys: Vec<[u8; 16]>,
+}
+
+impl Aes {
+    /// 16, 24 or 32 byte key
+    pub fn new(key: &[u8]) -> Aes {
+        assert!(matches!(key.len(), 16 | 24 | 32), "invalid AES key length");
+        let nk = key.len() / 4;
+        let rounds = nk + 6;
+        // key expansion, FIPS 197 5.2
+        let mut w: Vec<[u8; 4]> = key.chunks(4).map(|c| [c[0], c[1], c[2], c[3]]).collect();
+        let mut rcon = 1u8;
+        for i in nk..4 * (rounds + 1) {
+            let mut t = w[i - 1];
+            if i % nk == 0 {
+                t = [SBOX[t[1] as usize] ^ rcon, SBOX[t[2] as usize], SBOX[t[3] as usize], SBOX[t[0] as usize]];
+                rcon = xtime(rcon);
+            } else if nk > 6 && i % nk == 4 {
+                t = [SBOX[t[0] as usize], SBOX[t[1] as usize], SBOX[t[2] as usize], SBOX[t[3] as usize]];
+            }
+            let prev = w[i - nk];
+            w.push([prev[0] ^ t[0], prev[1] ^ t[1], prev[2] ^ t[2], prev[3] ^ t[3]]);
+        }
+        let round_keys = w.chunks(4).map(|words| {
+            let mut k = [0u8; 16];
+            for (j, word) in words.iter().enumerate() {
+                k[4 * j..4 * j + 4].copy_from_slice(word);
+            }
+            k
+        }).collect();
+        Aes { round_keys }
+    }
+
+    pub fn encrypt_block(&self, block: &[u8; 16]) -> [u8; 16] {
+        let rounds = self.round_keys.len() - 1;
+        let mut s = *block;
+        add_round_key(&mut s, &self.round_keys[0]);
+        for round in 1..=rounds {
+            for b in s.iter_mut() {
+                *b = SBOX[*b as usize];
+            }
+            shift_rows(&mut s);
+            if round != rounds {
+                mix_columns(&mut s);
+            }
+            add_round_key(&mut s, &self.round_keys[round]);
+        }
+        s
+    }
+
+    /// CTR mode with a 128-bit big-endian counter starting at iv, encryption and decryption alike
+    pub fn ctr(&self, iv: &[u8; 16], data: &[u8]) -> Vec<u8> {
+        let mut counter = u128::from_be_bytes(*iv);
+        let mut out: Vec<u8> = Vec::with_capacity(data.len());
+        for chunk in data.chunks(16) {
+            let keystream = self.encrypt_block(&counter.to_be_bytes());
+            out.extend(chunk.iter().zip(keystream.iter()).map(|(a, b)| a ^ b));
+            counter = counter.wrapping_add(1);
+        }
+        out
+    }
+}
+
+fn add_round_key(s: &mut [u8; 16], k: &[u8; 16]) {
+    for (b, k) in s.iter_mut().zip(k.iter()) {
+        *b ^= k;
+    }
+}
+
+/// the state is column-major, s[r + 4 c]
+fn shift_rows(s: &mut [u8; 16]) {
+    let t = *s;
+    for r in 1..4 {
+        for c in 0..4 {
+            s[r + 4 * c] = t[r + 4 * ((c + r) % 4)];
+        }
+    }
+}
+
+fn mix_columns(s: &mut [u8; 16]) {
+    for c in 0..4 {
+        let col = [s[4 * c], s[4 * c + 1], s[4 * c + 2], s[4 * c + 3]];
+        let all = col[0] ^ col[1] ^ col[2] ^ col[3];
+        for r in 0..4 {
+            s[4 * c + r] = col[r] ^ all ^ xtime(col[r] ^ col[(r + 1) % 4]);
+        }
+    }
+}
+
+#[test]
+fn aes_test() {
+    use super::hash::{from_hex, to_hex};
+    // FIPS 197 appendix C
+    let plaintext: Vec<u8> = from_hex("00112233445566778899aabbccddeeff").unwrap();
+    let mut block = [0u8; 16];
+    block.copy_from_slice(&plaintext);
+    let vectors = [
+        ("000102030405060708090a0b0c0d0e0f", "69c4e0d86a7b0430d8cdb78070b4c55a"),
+        ("000102030405060708090a0b0c0d0e0f1011121314151617", "dda97ca4864cdfe06eaf70a0ec0d7191"),
+        ("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f", "8ea2b7ca516745bfeafc49904b496089"),
+    ];
+    for (key, expected) in vectors.iter() {
+        assert_eq!(to_hex(&Aes::new(&from_hex(key).unwrap()).encrypt_block(&block)), *expected);
+    }
+    // 2.5 blocks of keystream
+    let key: Vec<u8> = (0..16).collect();
+    let keystream = Aes::new(&key).ctr(&[0; 16], &[0; 40]);
+    assert_eq!(to_hex(&keystream),
+        "c6a13b37878f5b826f4f8162a1c8d8797346139595c0b41e497bbde365f42d0a49d68753999ba68c");
+}
@@ -0,0 +1,244 @@
+use num_bigint::{BigInt, Sign};
+use std::fmt;
+
+/// universal tags used by key formats
+pub const INTEGER: u8 = 0x02;
+pub const BIT_STRING: u8 = 0x03;
+pub const OCTET_STRING: u8 = 0x04;
+pub const OBJECT_IDENTIFIER: u8 = 0x06;
+pub const SEQUENCE: u8 = 0x30;
+
+/// constructed context-specific tag [n]
+pub fn context(n: u8) -> u8 {
+    0xa0 | n
+}
+
+/// reason DER input is rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Asn1Error {
+    Truncated,
+    UnexpectedTag { expected: u8, found: u8 },
+    /// indefinite or non-minimal length
+    InvalidLength,
+    /// malformed INTEGER, OBJECT IDENTIFIER or BIT STRING contents
+    InvalidValue,
+    TrailingData,
+}
+
+impl fmt::Display for Asn1Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Asn1Error::Truncated => write!(f, "truncated DER"),
+            Asn1Error::UnexpectedTag { expected, found } =>
+                write!(f, "expected tag {:02x}, found {:02x}", expected, found),
+            Asn1Error::InvalidLength => write!(f, "invalid DER length"),
+            Asn1Error::InvalidValue => write!(f, "invalid DER value"),
+            Asn1Error::TrailingData => write!(f, "trailing data after DER value"),
+        }
+    }
+}
+
+/// tag || length || content
+pub fn encode(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes: Vec<u8> = len.to_be_bytes().iter().copied().skip_while(|b| *b == 0).collect();
+        out.push(0x80 | bytes.len() as u8);
+        out.extend_from_slice(&bytes);
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+/// SEQUENCE of already encoded values
+pub fn encode_sequence(values: &[Vec<u8>]) -> Vec<u8> {
+    encode(SEQUENCE, &values.concat())
+}
+
+/// INTEGER of a non-negative value, minimal two's complement
+pub fn encode_unsigned(v: &BigInt) -> Vec<u8> {
+    assert!(v.sign() != Sign::Minus, "negative INTEGER");
+    let (_, mut bytes) = v.to_bytes_be();
+    if bytes[0] & 0x80 != 0 {
+        bytes.insert(0, 0);
+    }
+    encode(INTEGER, &bytes)
+}
+
+/// BIT STRING with no unused bits
+pub fn encode_bit_string(bytes: &[u8]) -> Vec<u8> {
+    encode(BIT_STRING, &[&[0], bytes].concat())
+}
+
+/// OBJECT IDENTIFIER of a dotted string such as "1.2.840.10045.2.1"
+pub fn encode_oid(oid: &str) -> Vec<u8> {
+    let arcs: Vec<u64> = oid.split('.').map(|arc| arc.parse().expect("invalid OID")).collect();
+    assert!(arcs.len() >= 2 && arcs[0] <= 2, "invalid OID");
+    let mut content: Vec<u8> = Vec::new();
+    for arc in std::iter::once(40 * arcs[0] + arcs[1]).chain(arcs[2..].iter().copied()) {
+        // base 128, high bit set on all but the last byte
+        let mut base128 = vec![(arc & 0x7f) as u8];
+        let mut rest = arc >> 7;
+        while rest > 0 {
+            base128.insert(0, 0x80 | (rest & 0x7f) as u8);
+            rest >>= 7;
+        }
+        content.extend_from_slice(&base128);
+    }
+    encode(OBJECT_IDENTIFIER, &content)
+}
+
+/// dotted string of OBJECT IDENTIFIER contents
+pub fn decode_oid(content: &[u8]) -> Result<String, Asn1Error> {
+    if content.is_empty() || content[content.len() - 1] & 0x80 != 0 {
+        return Err(Asn1Error::InvalidValue);
+    }
+    let mut arcs: Vec<u64> = Vec::new();
+    let mut arc = 0u64;
+    for (i, b) in content.iter().enumerate() {
+        let starts_arc = i == 0 || content[i - 1] & 0x80 == 0;
+        if (starts_arc && *b == 0x80) || arc >> 57 != 0 {
+            return Err(Asn1Error::InvalidValue);
+        }
+        arc = (arc << 7) | (b & 0x7f) as u64;
+        if b & 0x80 == 0 {
+            if arcs.is_empty() {
+                let first = (arc / 40).min(2);
+                arcs.push(first);
+                arcs.push(arc - 40 * first);
+            } else {
+                arcs.push(arc);
+            }
+            arc = 0;
+        }
+    }
+    Ok(arcs.iter().map(|arc| arc.to_string()).collect::<Vec<String>>().join("."))
+}
+
+/// sequential reader of DER values
+#[derive(Debug, Clone)]
+pub struct DerReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> DerReader<'a> {
+    pub fn new(data: &'a [u8]) -> DerReader<'a> {
+        DerReader { data }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn peek_tag(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    /// Err(TrailingData) unless all input has been read
+    pub fn finish(&self) -> Result<(), Asn1Error> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(Asn1Error::TrailingData)
+        }
+    }
+
+    /// content of the next value, which must have the given tag
+    pub fn read(&mut self, tag: u8) -> Result<&'a [u8], Asn1Error> {
+        let found = self.peek_tag().ok_or(Asn1Error::Truncated)?;
+        if found != tag {
+            return Err(Asn1Error::UnexpectedTag { expected: tag, found });
+        }
+        let first = *self.data.get(1).ok_or(Asn1Error::Truncated)?;
+        let (len, header) = if first < 0x80 {
+            (first as usize, 2)
+        } else {
+            let count = (first & 0x7f) as usize;
+            if count == 0 || count > 4 {
+                return Err(Asn1Error::InvalidLength);
+            }
+            let bytes = self.data.get(2..2 + count).ok_or(Asn1Error::Truncated)?;
+            let len = bytes.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+            if bytes[0] == 0 || len < 0x80 {
+                return Err(Asn1Error::InvalidLength);
+            }
+            (len, 2 + count)
+        };
+        let content = self.data.get(header..header + len).ok_or(Asn1Error::Truncated)?;
+        self.data = &self.data[header + len..];
+        Ok(content)
+    }
+
+    /// content of the next value if it has the given tag
+    pub fn read_optional(&mut self, tag: u8) -> Result<Option<&'a [u8]>, Asn1Error> {
+        if self.peek_tag() == Some(tag) {
+            self.read(tag).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// reader over the contents of the next SEQUENCE
+    pub fn read_sequence(&mut self) -> Result<DerReader<'a>, Asn1Error> {
+        self.read(SEQUENCE).map(DerReader::new)
+    }
+
+    /// non-negative INTEGER
+    pub fn read_unsigned(&mut self) -> Result<BigInt, Asn1Error> {
+        let content = self.read(INTEGER)?;
+        let minimal = match content {
+            [] => false,
+            [0, next, ..] => next & 0x80 != 0,
+            [first, ..] => first & 0x80 == 0,
+        };
+        if !minimal {
+            return Err(Asn1Error::InvalidValue);
+        }
+        Ok(BigInt::from_bytes_be(Sign::Plus, content))
+    }
+
+    pub fn read_oid(&mut self) -> Result<String, Asn1Error> {
+        decode_oid(self.read(OBJECT_IDENTIFIER)?)
+    }
+
+    /// BIT STRING with no unused bits
+    pub fn read_bit_string(&mut self) -> Result<&'a [u8], Asn1Error> {
+        match self.read(BIT_STRING)? {
+            [0, bytes @ ..] => Ok(bytes),
+            _ => Err(Asn1Error::InvalidValue),
+        }
+    }
+}
+
+#[test]
+fn asn1_test() {
+    use super::hash::to_hex;
+    assert_eq!(to_hex(&encode_oid("1.2.840.10045.2.1")), "06072a8648ce3d0201");
+    assert_eq!(to_hex(&encode_oid("1.3.132.0.10")), "06052b8104000a");
+    assert_eq!(decode_oid(&encode_oid("2.999.3")[2..]), Ok("2.999.3".to_string()));
+    assert_eq!(decode_oid(&[0x2a, 0x86]), Err(Asn1Error::InvalidValue));
+    assert_eq!(to_hex(&encode_unsigned(&BigInt::from(0))), "020100");
+    assert_eq!(to_hex(&encode_unsigned(&BigInt::from(128))), "02020080");
+    assert_eq!(to_hex(&encode(OCTET_STRING, &[0; 200])[..3]), "0481c8");
+
+    let der = encode_sequence(&[encode_unsigned(&BigInt::from(1)), encode(context(0), &encode_oid("1.3.132.0.10")),
+        encode_bit_string(&[4, 5])]);
+    let mut outer = DerReader::new(&der);
+    let mut reader = outer.read_sequence().unwrap();
+    assert!(outer.finish().is_ok());
+    assert_eq!(reader.read_unsigned(), Ok(BigInt::from(1)));
+    assert_eq!(reader.read_optional(context(1)), Ok(None));
+    let mut params = DerReader::new(reader.read_optional(context(0)).unwrap().unwrap());
+    assert_eq!(params.read_oid(), Ok("1.3.132.0.10".to_string()));
+    assert_eq!(reader.read(OCTET_STRING), Err(Asn1Error::UnexpectedTag { expected: OCTET_STRING, found: BIT_STRING }));
+    assert_eq!(reader.read_bit_string(), Ok(&[4u8, 5][..]));
+    assert!(reader.finish().is_ok());
+
+    // non-minimal integer and length, truncation
+    assert_eq!(DerReader::new(&[2, 2, 0, 1]).read_unsigned(), Err(Asn1Error::InvalidValue));
+    assert_eq!(DerReader::new(&[4, 0x81, 1, 0]).read(OCTET_STRING), Err(Asn1Error::InvalidLength));
+    assert_eq!(DerReader::new(&[4, 3, 0]).read(OCTET_STRING), Err(Asn1Error::Truncated));
+}
@@ -0,0 +1,88 @@
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+use num_traits::{ToPrimitive, Zero};
+use std::fmt;
+use super::hash::sha256;
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// reason a Base58 string is rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base58Error {
+    InvalidCharacter(char),
+    /// shorter than the 4 checksum bytes
+    TooShort,
+    InvalidChecksum,
+}
+
+impl fmt::Display for Base58Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Base58Error::InvalidCharacter(c) => write!(f, "invalid Base58 character {:?}", c),
+            Base58Error::TooShort => write!(f, "Base58Check string too short"),
+            Base58Error::InvalidChecksum => write!(f, "invalid Base58Check checksum"),
+        }
+    }
+}
+
+/// Bitcoin Base58, each leading zero byte is a '1'
+pub fn encode(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|b| **b == 0).count();
+    let mut n = BigInt::from_bytes_be(Sign::Plus, bytes);
+    let base = BigInt::from(58);
+    let mut digits: Vec<u8> = Vec::new();
+    while !n.is_zero() {
+        let (q, r) = n.div_rem(&base);
+        digits.push(ALPHABET[r.to_usize().unwrap()]);
+        n = q;
+    }
+    digits.extend(std::iter::repeat_n(b'1', zeros));
+    digits.reverse();
+    String::from_utf8(digits).unwrap()
+}
+
+pub fn decode(s: &str) -> Result<Vec<u8>, Base58Error> {
+    let mut n: BigInt = Zero::zero();
+    for c in s.chars() {
+        let digit = ALPHABET.iter().position(|a| *a as char == c).ok_or(Base58Error::InvalidCharacter(c))?;
+        n = n * 58 + digit;
+    }
+    let zeros = s.chars().take_while(|c| *c == '1').count();
+    let mut bytes = vec![0u8; zeros];
+    if !n.is_zero() {
+        bytes.extend_from_slice(&n.to_bytes_be().1);
+    }
+    Ok(bytes)
+}
+
+/// data || first 4 bytes of SHA-256(SHA-256(data))
+pub fn encode_check(data: &[u8]) -> String {
+    let checksum = sha256(&sha256(data));
+    encode(&[data, &checksum[..4]].concat())
+}
+
+pub fn decode_check(s: &str) -> Result<Vec<u8>, Base58Error> {
+    let mut bytes = decode(s)?;
+    if bytes.len() < 4 {
+        return Err(Base58Error::TooShort);
+    }
+    let checksum = bytes.split_off(bytes.len() - 4);
+    if sha256(&sha256(&bytes))[..4] != checksum[..] {
+        return Err(Base58Error::InvalidChecksum);
+    }
+    Ok(bytes)
+}
+
+#[test]
+fn base58_test() {
+    assert_eq!(encode(b"hello world"), "StV1DL6CwTryKyV");
+    assert_eq!(encode(&[0, 0, 1, 2]), "115T");
+    assert_eq!(encode(&[]), "");
+    assert_eq!(decode("115T"), Ok(vec![0, 0, 1, 2]));
+    assert_eq!(decode("StV1DL6CwTryKyV"), Ok(b"hello world".to_vec()));
+    assert_eq!(decode("0OIl"), Err(Base58Error::InvalidCharacter('0')));
+    assert_eq!(encode_check(&[0; 21]), "1111111111111111111114oLvT2");
+    assert_eq!(decode_check("1111111111111111111114oLvT2"), Ok(vec![0; 21]));
+    assert_eq!(decode_check("1111111111111111111114oLvT3"), Err(Base58Error::InvalidChecksum));
+    assert_eq!(decode_check("1"), Err(Base58Error::TooShort));
+}
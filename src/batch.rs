@@ -0,0 +1,234 @@
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use rand::Rng;
+use crate::bigint::inverse_mod;
+use super::curve::Curve;
+use super::ecdsa::{Ecdsa, Signature};
+use super::elliptic_curve::ECPoint;
+use super::hash::tagged_hash;
+use super::schnorr::{SchnorrSignature, XOnlyPublicKey};
+use super::secp256k1::Secp256k1;
+
+/// BIP-340 signature to verify in a batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchnorrBatchItem {
+    pub public_key: XOnlyPublicKey,
+    pub msg: Vec<u8>,
+    pub signature: SchnorrSignature,
+}
+
+/// ECDSA signature to verify in a batch
+/// recovery_id is the one of sign_hash_recoverable, R = k G is lifted from x = r + (id >> 1) n and the parity id & 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EcdsaBatchItem {
+    pub public_key: ECPoint,
+    pub hash: Vec<u8>,
+    pub signature: Signature,
+    pub recovery_id: u8,
+}
+
+/// random multiplier in [1, n), the first one is 1
+fn multipliers<R: Rng + ?Sized>(rng: &mut R, count: usize, n: &BigInt) -> Vec<BigInt> {
+    let mut a: Vec<BigInt> = Vec::with_capacity(count);
+    if count > 0 {
+        a.push(One::one());
+    }
+    while a.len() < count {
+        let bytes: [u8; 32] = rng.gen();
+        let ai = BigInt::from_bytes_be(Sign::Plus, &bytes).mod_floor(n);
+        if !ai.is_zero() {
+            a.push(ai);
+        }
+    }
+    a
+}
+
+/// Err(i) for the first item failing verify
+fn first_invalid<T, F: Fn(&T) -> bool>(items: &[T], verify: F) -> Result<(), usize> {
+    match items.iter().position(|item| !verify(item)) {
+        Some(i) => Err(i),
+        None => Ok(()),
+    }
+}
+
+impl Secp256k1 {
+    /// BIP-340 batch verification
+    /// (a1 s1 + ... + au su) G = a1 R1 + ... + au Ru + a1 e1 P1 + ... + au eu Pu
+    /// with a1 = 1 and random ai, checked by one multi-scalar multiplication
+    /// Err(i) is the first invalid signature, found by verifying one by one
+    /// when the batch fails or an item can not be parsed
+    pub fn schnorr_batch_verify<R: Rng + ?Sized>(&self, items: &[SchnorrBatchItem], rng: &mut R) -> Result<(), usize> {
+        let n = &self.n;
+        let a = multipliers(rng, items.len(), n);
+        let mut points: Vec<ECPoint> = vec![self.g.clone()];
+        let mut scalars: Vec<BigInt> = vec![Zero::zero()];
+        let one_by_one = || first_invalid(items, |item| self.schnorr_verify(&item.public_key, &item.msg, &item.signature));
+        for (i, item) in items.iter().enumerate() {
+            let mut r_bytes = [0u8; 32];
+            r_bytes.copy_from_slice(&item.signature[..32]);
+            let s = BigInt::from_bytes_be(Sign::Plus, &item.signature[32..]);
+            let (p, r) = match (self.lift_x(&item.public_key), self.lift_x(&r_bytes)) {
+                (Some(p), Some(r)) if &s < n => (p, r),
+                _ => return one_by_one(),
+            };
+            let challenge = [&item.signature[..32], &item.public_key[..], &item.msg].concat();
+            let e = BigInt::from_bytes_be(Sign::Plus, &tagged_hash("BIP0340/challenge", &challenge));
+            scalars[0] = (&scalars[0] + &a[i] * s).mod_floor(n);
+            points.push(r.into());
+            scalars.push(n - &a[i]);
+            points.push(p.into());
+            scalars.push((-(&a[i] * e)).mod_floor(n));
+        }
+        if self.ec.multi_scalar_multiply(&points, &scalars).is_infinity() {
+            return Ok(());
+        }
+        one_by_one()
+    }
+}
+
+impl Ecdsa {
+    /// R of a batch item, None if it can not be lifted
+    /// on curves with a cofactor R and Q must lie in the subgroup of order n
+    fn batch_point_r(&self, item: &EcdsaBatchItem) -> Option<ECPoint> {
+        let n = &self.params.n;
+        if item.recovery_id > 3 {
+            return None;
+        }
+        let x = if item.recovery_id & 2 != 0 { &item.signature.r + n } else { item.signature.r.clone() };
+        if x >= self.params.p {
+            return None;
+        }
+        let point_r = self.curve().lift_x(&x, item.recovery_id & 1 == 1)?;
+        if !self.params.h.is_one() {
+            let in_subgroup = |point: &ECPoint| self.curve().multiply_scalar(point, n).is_infinity();
+            if !in_subgroup(&point_r) || !in_subgroup(&item.public_key) {
+                return None;
+            }
+        }
+        Some(point_r)
+    }
+
+    /// ECDSA batch verification with R recovered from (r, recovery_id)
+    /// a1 (u1 G + v1 Q1 - R1) + ... + au (uu G + vu Qu - Ru) = O, ui = ei / si, vi = ri / si
+    /// Err(i) is the first invalid signature, found by verifying one by one
+    /// when the batch fails or an item can not be parsed
+    pub fn batch_verify<R: Rng + ?Sized>(&self, items: &[EcdsaBatchItem], rng: &mut R) -> Result<(), usize> {
+        let n = &self.params.n;
+        let a = multipliers(rng, items.len(), n);
+        let mut points: Vec<ECPoint> = vec![self.params.g.clone()];
+        let mut scalars: Vec<BigInt> = vec![Zero::zero()];
+        let one_by_one = || first_invalid(items, |item| self.verify_hash(&item.public_key, &item.hash, &item.signature));
+        for (i, item) in items.iter().enumerate() {
+            let (r, s) = (&item.signature.r, &item.signature.s);
+            let in_range = |v: &BigInt| v > &Zero::zero() && v < n;
+            let q = &item.public_key;
+            if !in_range(r) || !in_range(s) || q.is_infinity() || !self.curve().is_on_curve(q) {
+                return one_by_one();
+            }
+            let point_r = match self.batch_point_r(item) {
+                Some(point_r) => point_r,
+                None => return one_by_one(),
+            };
+            let w = inverse_mod(s, n).unwrap();
+            let e = self.bits_to_int(&item.hash);
+            scalars[0] = (&scalars[0] + &a[i] * e * &w).mod_floor(n);
+            points.push(q.clone());
+            scalars.push((&a[i] * r * &w).mod_floor(n));
+            points.push(point_r);
+            scalars.push(n - &a[i]);
+        }
+        if self.curve().multi_scalar_multiply(&points, &scalars).is_infinity() {
+            return Ok(());
+        }
+        one_by_one()
+    }
+}
+
+#[test]
+fn schnorr_batch_verify_test() {
+    use super::scalar::Scalar;
+    use rand::{SeedableRng, rngs::StdRng};
+    let secp = Secp256k1::new();
+    let mut rng = StdRng::seed_from_u64(44);
+    let mut items: Vec<SchnorrBatchItem> = (0..4u8).map(|i| {
+        let sk = Scalar::random(&mut rng);
+        let msg = vec![i; 32];
+        SchnorrBatchItem {
+            public_key: secp.x_only_public_key(&sk),
+            signature: secp.schnorr_sign(&sk, &msg, &[i; 32]),
+            msg,
+        }
+    }).collect();
+    assert_eq!(secp.schnorr_batch_verify(&items, &mut rng), Ok(()));
+    assert_eq!(secp.schnorr_batch_verify(&[], &mut rng), Ok(()));
+
+    let mut bad = items.clone();
+    bad[2].msg[0] ^= 1;
+    assert_eq!(secp.schnorr_batch_verify(&bad, &mut rng), Err(2));
+    // s out of range goes straight to the one by one verification
+    items[1].signature[32..].copy_from_slice(&[0xff; 32]);
+    assert_eq!(secp.schnorr_batch_verify(&items, &mut rng), Err(1));
+    // and still reports the first invalid signature
+    items[0].msg[0] ^= 1;
+    assert_eq!(secp.schnorr_batch_verify(&items, &mut rng), Err(0));
+}
+
+#[cfg(test)]
+fn ecdsa_batch_items(ecdsa: &Ecdsa, keys: &[BigInt]) -> Vec<EcdsaBatchItem> {
+    keys.iter().enumerate().map(|(i, d)| {
+        let hash = super::hash::sha256(&[i as u8]).to_vec();
+        let (signature, recovery_id) = ecdsa.sign_hash_recoverable(d, &hash);
+        EcdsaBatchItem { public_key: ecdsa.public_key(d), hash, signature, recovery_id }
+    }).collect()
+}
+
+#[test]
+fn ecdsa_batch_verify_test() {
+    use super::domain_parameters::DomainParameters;
+    use rand::{SeedableRng, rngs::StdRng};
+    let ecdsa = Ecdsa::new(&DomainParameters::secp256k1());
+    let mut rng = StdRng::seed_from_u64(44);
+    let keys: Vec<BigInt> = (0..3).map(|_| ecdsa.generate_key(&mut rng).0).collect();
+    let mut items = ecdsa_batch_items(&ecdsa, &keys);
+    assert_eq!(ecdsa.batch_verify(&items, &mut rng), Ok(()));
+
+    let mut bad = items.clone();
+    bad[2].hash[0] ^= 1;
+    assert_eq!(ecdsa.batch_verify(&bad, &mut rng), Err(2));
+    // s out of range behind an invalid signature
+    bad[0].hash[0] ^= 1;
+    bad[1].signature.s = ecdsa.params.n.clone();
+    assert_eq!(ecdsa.batch_verify(&bad, &mut rng), Err(0));
+    // a wrong recovery id fails the batch but not the fallback
+    items[0].recovery_id ^= 1;
+    assert_eq!(ecdsa.batch_verify(&items, &mut rng), Ok(()));
+    items[1].recovery_id = 7;
+    assert_eq!(ecdsa.batch_verify(&items, &mut rng), Ok(()));
+}
+
+#[test]
+fn ecdsa_batch_verify_cofactor_test() {
+    use super::domain_parameters::DomainParameters;
+    use rand::{SeedableRng, rngs::StdRng};
+    // y^2 = x^3 + x over GF(283), 284 = 4 * 71 points, R.x >= n for most nonces
+    let params = DomainParameters {
+        name: "small",
+        oid: "",
+        p: BigInt::from(283),
+        a: BigInt::from(1),
+        b: BigInt::from(0),
+        g: ECPoint::new(&BigInt::from(159), &BigInt::from(39), &One::one()),
+        n: BigInt::from(71),
+        h: BigInt::from(4),
+    };
+    let ecdsa = Ecdsa::new(&params);
+    let mut rng = StdRng::seed_from_u64(44);
+    let keys: Vec<BigInt> = (2..18).map(BigInt::from).collect();
+    let mut items = ecdsa_batch_items(&ecdsa, &keys);
+    assert!(items.iter().any(|item| item.recovery_id & 2 != 0));
+    assert_eq!(ecdsa.batch_verify(&items, &mut rng), Ok(()));
+    items[5].hash[0] ^= 0x80;
+    items[9].signature.s = Zero::zero();
+    assert_eq!(ecdsa.batch_verify(&items, &mut rng), Err(5));
+}
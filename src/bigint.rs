@@ -2,6 +2,7 @@ use num_integer::Integer;
 use num_bigint::BigInt;
 use num_traits::Zero;
 use num_traits::One;
+use crate::modular::MontgomeryContext;
 
 /// T^n
 /// NOTE: BigInt::Pow is not enough functionality, so implement by myself.
@@ -11,7 +12,11 @@ pub trait Power<T> {
 
 /// T^n (mod p)
 pub trait PowerModulo {
+    /// convenience for a one-off p, builds a MontgomeryContext on each call when p has 64 bits or more
     fn power_modulo(&self, n: &BigInt, p: &BigInt) -> Self;
+
+    /// T^n (mod ctx.n) with a context reused across calls, n >= 0
+    fn power_modulo_with(&self, n: &BigInt, ctx: &MontgomeryContext) -> Self;
 }
 
 /// 1/T (mod p)
@@ -65,8 +70,30 @@ impl Power<i32> for BigInt {
     }
 }
 
+/// MontgomeryContext for the moduli power_modulo uses one for, odd p of 64 bits or more
+pub fn montgomery_context(p: &BigInt) -> Option<MontgomeryContext> {
+    if p.is_odd() && p.bits() >= 64 {
+        Some(MontgomeryContext::new(p))
+    } else {
+        None
+    }
+}
+
+/// x^n (mod p) with ctx = montgomery_context(p) built once by the caller
+pub(crate) fn power_modulo_cached(x: &BigInt, n: &BigInt, p: &BigInt, ctx: Option<&MontgomeryContext>) -> BigInt {
+    match ctx {
+        Some(ctx) if n > &Zero::zero() => x.power_modulo_with(n, ctx),
+        _ => x.power_modulo(n, p),
+    }
+}
+
 impl PowerModulo for BigInt {
     fn power_modulo(&self, n: &BigInt, p: &BigInt) -> Self {
+        if n > &Zero::zero() {
+            if let Some(ctx) = montgomery_context(p) {
+                return self.power_modulo_with(n, &ctx);
+            }
+        }
         if n == &Zero::zero() {
             return One::one();
         }
@@ -84,6 +111,10 @@ impl PowerModulo for BigInt {
         }
         return (r * b).mod_floor(p);
     }
+
+    fn power_modulo_with(&self, n: &BigInt, ctx: &MontgomeryContext) -> Self {
+        ctx.power(self, n)
+    }
 }
 
 impl Inverse for BigInt {
@@ -110,6 +141,132 @@ pub fn extended_gcd(a: BigInt, b: BigInt) -> (BigInt, BigInt, BigInt) {
     (g, y - q * x.clone(), x.clone())
 }
 
+/// 1/a (mod m) by the iterative extended euclid algorithm
+/// m need not be prime, returns None if gcd(a, m) != 1
+pub fn inverse_mod(a: &BigInt, m: &BigInt) -> Option<BigInt> {
+    assert!(m >= &BigInt::from(2));
+    // invariant: r0 = s0 a (mod m), r1 = s1 a (mod m)
+    let (mut r0, mut r1) = (m.clone(), a.mod_floor(m));
+    let (mut s0, mut s1): (BigInt, BigInt) = (Zero::zero(), One::one());
+    while !r1.is_zero() {
+        let (q, r2) = r0.div_rem(&r1);
+        let s2 = s0 - &q * &s1;
+        r0 = std::mem::replace(&mut r1, r2);
+        s0 = std::mem::replace(&mut s1, s2);
+    }
+    if !r0.is_one() {
+        return None;
+    }
+    Some(s0.mod_floor(m))
+}
+
+/// Miller-Rabin test with the first 20 primes as bases
+/// deterministic for n < 3.3 * 10^24, error below 4^-20 otherwise
+pub fn is_probable_prime(n: &BigInt) -> bool {
+    const BASES: [u32; 20] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71];
+    if n < &BigInt::from(2) {
+        return false;
+    }
+    for b in BASES.iter() {
+        let b = BigInt::from(*b);
+        if n == &b {
+            return true;
+        }
+        if n.is_multiple_of(&b) {
+            return false;
+        }
+    }
+    // n - 1 = 2^s d, d odd
+    let n1: BigInt = n - 1;
+    let mut d = n1.clone();
+    let mut s = 0;
+    while d.is_even() {
+        d >>= 1;
+        s += 1;
+    }
+    let ctx = montgomery_context(n);
+    'witness: for b in BASES.iter() {
+        let mut x = power_modulo_cached(&BigInt::from(*b), &d, n, ctx.as_ref());
+        if x.is_one() || x == n1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = (&x * &x).mod_floor(n);
+            if x == n1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// r-th root of T (mod p)
+/// r and p must be prime, returns None if T is not a r-th power
+pub trait RootModulo: Sized {
+    fn root_modulo(&self, r: u32, p: &BigInt) -> Option<Self>;
+
+    fn sqrt_modulo(&self, p: &BigInt) -> Option<Self> {
+        self.root_modulo(2, p)
+    }
+}
+
+/// Tonelli-Shanks algorithm generalized to r-th roots
+impl RootModulo for BigInt {
+    fn root_modulo(&self, r: u32, p: &BigInt) -> Option<Self> {
+        let a = self.mod_floor(p);
+        if a.is_zero() || p == &BigInt::from(2) {
+            return Some(a);
+        }
+        let ctx = montgomery_context(p);
+        let pow = |x: &BigInt, n: &BigInt| power_modulo_cached(x, n, p, ctx.as_ref());
+        let rr = BigInt::from(r);
+        let pm1: BigInt = p - 1;
+        // p - 1 = r^e q
+        let mut q = pm1.clone();
+        let mut e: u32 = 0;
+        while q.is_multiple_of(&rr) {
+            q /= &rr;
+            e += 1;
+        }
+        if e == 0 {
+            // x -> x^r is a bijection
+            let (_, d, _) = extended_gcd(rr.clone(), pm1.clone());
+            return Some(pow(&a, &d.mod_floor(&pm1)));
+        }
+        let euler = &pm1 / &rr;
+        if pow(&a, &euler) != One::one() {
+            return None;
+        }
+        let mut z = BigInt::from(2);
+        while pow(&z, &euler) == One::one() {
+            z += 1;
+        }
+        // generator of the r-Sylow subgroup
+        let g = pow(&z, &q);
+        let g_inv = g.inverse(p);
+        // element of order r
+        let gamma = pow(&g, &rr.power(&BigInt::from(e - 1)));
+        // 1/r (mod q)
+        let (_, d, _) = extended_gcd(rr.clone(), q.clone());
+        let x0 = pow(&a, &d.mod_floor(&q));
+        // x0^r / a is in the r-Sylow subgroup, find y with y^r = a / x0^r
+        let target = (&a * pow(&x0, &rr).inverse(p)).mod_floor(p);
+        let mut l: BigInt = Zero::zero();
+        for i in 0..e {
+            let h = pow(&(&target * pow(&g_inv, &l)).mod_floor(p), &rr.power(&BigInt::from(e - 1 - i)));
+            let mut k: u32 = 0;
+            while pow(&gamma, &BigInt::from(k)) != h {
+                k += 1;
+                assert!(k < r, "discrete log not found");
+            }
+            l += BigInt::from(k) * rr.power(&BigInt::from(i));
+        }
+        let y = pow(&g, &(l / &rr));
+        Some((x0 * y).mod_floor(p))
+    }
+}
+
 /// x (mod l) = r
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct ModResult {
@@ -134,6 +291,46 @@ pub fn chinese_remainder(mod_result: &Vec<ModResult>) -> ModResult {
     ModResult { l, r }
 }
 
+/// Montgomery's simultaneous inversion
+/// 1/a1, 1/a2, ... 1/an (mod p) with one inversion and 3 (n - 1) multiplications
+/// zeros are left as zero
+pub fn batch_inverse(values: &[BigInt], p: &BigInt) -> Vec<BigInt> {
+    // prefix[i] = a1 a2 ... ai, skipping zeros
+    let mut prefix: Vec<BigInt> = Vec::with_capacity(values.len());
+    let mut acc: BigInt = One::one();
+    for v in values {
+        let v = v.mod_floor(p);
+        if !v.is_zero() {
+            acc = (acc * v).mod_floor(p);
+        }
+        prefix.push(acc.clone());
+    }
+    let mut inv = acc.inverse(p);
+    let mut result: Vec<BigInt> = vec![Zero::zero(); values.len()];
+    for i in (0..values.len()).rev() {
+        let v = values[i].mod_floor(p);
+        if v.is_zero() {
+            continue;
+        }
+        let before = if i == 0 { One::one() } else { prefix[i - 1].clone() };
+        result[i] = (&inv * before).mod_floor(p);
+        inv = (inv * v).mod_floor(p);
+    }
+    result
+}
+
+#[test]
+fn is_probable_prime_test() {
+    let primes: Vec<BigInt> = (0..100).map(BigInt::from).filter(is_probable_prime).collect();
+    assert_eq!(primes.len(), 25);
+    // Carmichael number and a strong pseudoprime to bases 2, 3, 5, 7
+    assert!(!is_probable_prime(&BigInt::from(561)));
+    assert!(!is_probable_prime(&BigInt::from(3_215_031_751u64)));
+    let p = BigInt::from(2).power(127) - 1;
+    assert!(is_probable_prime(&p));
+    assert!(!is_probable_prime(&(&p * &p)));
+}
+
 #[test]
 fn bigint_power_test() {
     let q = BigInt::from(2);
@@ -147,6 +344,14 @@ fn bigint_power_modulo_test() {
     assert_eq_str!(BigInt::from(3).power_modulo(&BigInt::from(2), &p), "4");
     assert_eq_str!(BigInt::from(3).power_modulo(&BigInt::from(3), &p), "2");
     assert_eq_str!(BigInt::from(3).power_modulo(&BigInt::from(4), &p), "1");
+    // 2^127 - 1
+    let p = (BigInt::from(1) << 127) - 1;
+    let ctx = montgomery_context(&p).unwrap();
+    for n in [0, 1, 2, 127, 1000] {
+        let n = BigInt::from(n);
+        assert_eq!(BigInt::from(3).power_modulo_with(&n, &ctx), BigInt::from(3).modpow(&n, &p));
+        assert_eq!(power_modulo_cached(&BigInt::from(3), &n, &p, Some(&ctx)), BigInt::from(3).modpow(&n, &p));
+    }
 }
 
 #[test]
@@ -191,6 +396,41 @@ fn bigint_divide_test() {
     assert_eq_str!((BigInt::from(-7).mod_floor(&BigInt::from(-3))), "-1"); 
 }
 
+#[test]
+fn root_modulo_test() {
+    // p = 13: p - 1 = 2^2 * 3
+    let p = BigInt::from(13);
+    for a in 0..13 {
+        let a = BigInt::from(a);
+        let is_square = (0..13).any(|x| BigInt::from(x * x).mod_floor(&p) == a);
+        match a.sqrt_modulo(&p) {
+            Some(x) => assert_eq!((&x * &x).mod_floor(&p), a),
+            None => assert!(!is_square),
+        }
+    }
+    assert_eq!(BigInt::from(5).sqrt_modulo(&p), None);
+    // p = 7: p - 1 = 2 * 3, cube roots are not unique
+    let p = BigInt::from(7);
+    assert!(BigInt::from(6).root_modulo(3, &p).is_some());
+    assert_eq!(BigInt::from(3).root_modulo(3, &p), None);
+    for a in 1..7 {
+        if let Some(x) = BigInt::from(a).root_modulo(3, &p) {
+            assert_eq!(x.power(3).mod_floor(&p), BigInt::from(a));
+        }
+    }
+    // p = 11: every element has a unique cube root
+    let p = BigInt::from(11);
+    for a in 0..11 {
+        let x = BigInt::from(a).root_modulo(3, &p).unwrap();
+        assert_eq!(x.power(3).mod_floor(&p), BigInt::from(a));
+    }
+    // p = 2^256 - 2^32 - 977
+    let p = BigInt::from(2).power(256) - BigInt::from(2).power(32) - BigInt::from(977);
+    let x = BigInt::from(2).sqrt_modulo(&p).unwrap();
+    assert_eq!((&x * &x).mod_floor(&p), BigInt::from(2));
+    assert_eq!(BigInt::from(7).sqrt_modulo(&p), None);
+}
+
 #[test]
 fn extended_euclid_test1() {
     let (g, x, y) = extended_gcd(BigInt::from(8), BigInt::from(11)); 
@@ -237,3 +477,36 @@ fn chinese_remainder_test() {
                });
 }
 
+#[test]
+fn batch_inverse_test() {
+    let p = BigInt::from(101);
+    let values: Vec<BigInt> = [3, 0, 7, 100, 202, 55].iter().map(|v| BigInt::from(*v)).collect();
+    let inverses = batch_inverse(&values, &p);
+    for (v, i) in values.iter().zip(inverses.iter()) {
+        if v.mod_floor(&p).is_zero() {
+            assert!(i.is_zero());
+        } else {
+            assert_eq!(i, &v.inverse(&p));
+        }
+    }
+    assert!(batch_inverse(&[], &p).is_empty());
+}
+
+#[test]
+fn inverse_mod_test() {
+    let p = BigInt::from(101);
+    for a in 1..101 {
+        let a = BigInt::from(a);
+        assert_eq!(inverse_mod(&a, &p), Some(a.inverse(&p)));
+    }
+    // composite modulus
+    assert_eq_str!(inverse_mod(&BigInt::from(7), &BigInt::from(40)).unwrap(), "23");
+    assert_eq_str!(inverse_mod(&BigInt::from(-7), &BigInt::from(40)).unwrap(), "17");
+    assert_eq!(inverse_mod(&BigInt::from(6), &BigInt::from(40)), None);
+    assert_eq!(inverse_mod(&BigInt::from(0), &BigInt::from(40)), None);
+    // 4096-bit modulus
+    let m = BigInt::from(2).power(4096) - BigInt::from(1);
+    let a = BigInt::from(2).power(4000) + BigInt::from(3);
+    let i = inverse_mod(&a, &m).unwrap();
+    assert!((a * i).mod_floor(&m).is_one());
+}
@@ -0,0 +1,472 @@
+use std::fmt;
+use super::base58::{self, Base58Error};
+use super::hash::{hash160, hmac_sha512};
+use super::key_format::Network;
+use super::scalar::Scalar;
+use super::secp256k1::Secp256k1Point;
+use super::u256::FieldElement;
+
+/// child numbers from 2^31 on are hardened
+pub const HARDENED: u32 = 0x8000_0000;
+
+/// reason a derivation or an extended key is rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bip32Error {
+    /// seed outside 16 to 64 bytes
+    InvalidSeedLength,
+    /// I_L >= n or a zero key, the caller proceeds with the next index
+    InvalidChild,
+    HardenedFromPublic,
+    /// depth above 255
+    DepthOverflow,
+    InvalidPath,
+    Base58(Base58Error),
+    /// not 78 bytes
+    InvalidLength,
+    /// unknown version or a private version with public key data and vice versa
+    InvalidVersion,
+    /// key data out of range or not a point, or a master key with a parent
+    InvalidKey,
+}
+
+impl fmt::Display for Bip32Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Bip32Error::InvalidSeedLength => write!(f, "seed must be 16 to 64 bytes"),
+            Bip32Error::InvalidChild => write!(f, "invalid child key, use the next index"),
+            Bip32Error::HardenedFromPublic => write!(f, "hardened derivation from a public key"),
+            Bip32Error::DepthOverflow => write!(f, "depth above 255"),
+            Bip32Error::InvalidPath => write!(f, "invalid derivation path"),
+            Bip32Error::Base58(e) => write!(f, "{}", e),
+            Bip32Error::InvalidLength => write!(f, "extended key must be 78 bytes"),
+            Bip32Error::InvalidVersion => write!(f, "invalid extended key version"),
+            Bip32Error::InvalidKey => write!(f, "invalid extended key"),
+        }
+    }
+}
+
+impl From<Base58Error> for Bip32Error {
+    fn from(e: Base58Error) -> Bip32Error {
+        Bip32Error::Base58(e)
+    }
+}
+
+/// version bytes of xprv / xpub and tprv / tpub
+fn version(network: Network, private: bool) -> [u8; 4] {
+    match (network, private) {
+        (Network::Mainnet, true) => [0x04, 0x88, 0xad, 0xe4],
+        (Network::Mainnet, false) => [0x04, 0x88, 0xb2, 0x1e],
+        (Network::Testnet, true) => [0x04, 0x35, 0x83, 0x94],
+        (Network::Testnet, false) => [0x04, 0x35, 0x87, 0xcf],
+    }
+}
+
+/// serP(P), 02 / 03 || x
+fn serialize_point(point: &Secp256k1Point) -> [u8; 33] {
+    let (x, y) = point.to_affine().expect("point at infinity");
+    let mut out = [0u8; 33];
+    out[0] = 2 + y.is_odd() as u8;
+    out[1..].copy_from_slice(&x.to_be_bytes());
+    out
+}
+
+/// (x : y : 1), so that equal keys compare equal
+fn normalize(point: &Secp256k1Point) -> Secp256k1Point {
+    let (x, y) = point.to_affine().expect("point at infinity");
+    Secp256k1Point::from_affine(&x, &y)
+}
+
+fn parse_point(bytes: &[u8]) -> Option<Secp256k1Point> {
+    let mut x = [0u8; 32];
+    x.copy_from_slice(&bytes[1..]);
+    let point = Secp256k1Point::lift_x(&FieldElement::from_be_bytes(&x)?)?;
+    match bytes[0] {
+        2 => Some(point),
+        3 => Some(point.negate()),
+        _ => None,
+    }
+}
+
+/// child numbers of "m/44'/0'/0'/0/1", hardened with ', h or H
+pub fn parse_path(path: &str) -> Result<Vec<u32>, Bip32Error> {
+    let mut components = path.split('/');
+    if components.next() != Some("m") {
+        return Err(Bip32Error::InvalidPath);
+    }
+    components.map(|component| {
+        let (digits, hardened) = match component.strip_suffix(['\'', 'h', 'H']) {
+            Some(digits) => (digits, true),
+            None => (component, false),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Bip32Error::InvalidPath);
+        }
+        let index: u32 = digits.parse().map_err(|_| Bip32Error::InvalidPath)?;
+        if index >= HARDENED {
+            return Err(Bip32Error::InvalidPath);
+        }
+        Ok(if hardened { index | HARDENED } else { index })
+    }).collect()
+}
+
+/// I = HMAC-SHA512(key, data) split into I_L as a scalar and I_R
+fn hmac_split(key: &[u8], data: &[u8]) -> Result<(Scalar, [u8; 32]), Bip32Error> {
+    let i = hmac_sha512(key, data);
+    let mut il = [0u8; 32];
+    il.copy_from_slice(&i[..32]);
+    let mut ir = [0u8; 32];
+    ir.copy_from_slice(&i[32..]);
+    let il = Scalar::from_be_bytes(&il).ok_or(Bip32Error::InvalidChild)?;
+    Ok((il, ir))
+}
+
+/// version || depth || parent fingerprint || child number || chain code || key data
+fn serialize(network: Network, private: bool, depth: u8, parent_fingerprint: &[u8; 4], child_number: u32,
+    chain_code: &[u8; 32], key: &[u8; 33]) -> String {
+    let data = [
+        &version(network, private)[..], &[depth], parent_fingerprint, &child_number.to_be_bytes(), chain_code, key,
+    ].concat();
+    base58::encode_check(&data)
+}
+
+/// fields of a 78-byte extended key, the key data still to be checked
+struct Serialized {
+    network: Network,
+    private: bool,
+    depth: u8,
+    parent_fingerprint: [u8; 4],
+    child_number: u32,
+    chain_code: [u8; 32],
+    key: [u8; 33],
+}
+
+fn deserialize(s: &str) -> Result<Serialized, Bip32Error> {
+    let data = base58::decode_check(s)?;
+    if data.len() != 78 {
+        return Err(Bip32Error::InvalidLength);
+    }
+    let (network, private) = [(Network::Mainnet, true), (Network::Mainnet, false), (Network::Testnet, true),
+        (Network::Testnet, false)].iter().copied()
+        .find(|(network, private)| version(*network, *private)[..] == data[..4])
+        .ok_or(Bip32Error::InvalidVersion)?;
+    let mut serialized = Serialized {
+        network,
+        private,
+        depth: data[4],
+        parent_fingerprint: [0; 4],
+        child_number: u32::from_be_bytes([data[9], data[10], data[11], data[12]]),
+        chain_code: [0; 32],
+        key: [0; 33],
+    };
+    serialized.parent_fingerprint.copy_from_slice(&data[5..9]);
+    serialized.chain_code.copy_from_slice(&data[13..45]);
+    serialized.key.copy_from_slice(&data[45..]);
+    if serialized.depth == 0 && (serialized.parent_fingerprint != [0; 4] || serialized.child_number != 0) {
+        return Err(Bip32Error::InvalidKey);
+    }
+    Ok(serialized)
+}
+
+/// BIP-32 extended private key on secp256k1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedPrivateKey {
+    pub network: Network,
+    pub depth: u8,
+    pub parent_fingerprint: [u8; 4],
+    pub child_number: u32,
+    pub chain_code: [u8; 32],
+    pub secret_key: Scalar,
+}
+
+/// BIP-32 extended public key on secp256k1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedPublicKey {
+    pub network: Network,
+    pub depth: u8,
+    pub parent_fingerprint: [u8; 4],
+    pub child_number: u32,
+    pub chain_code: [u8; 32],
+    /// affine, z = 1
+    pub public_key: Secp256k1Point,
+}
+
+impl ExtendedPrivateKey {
+    /// master key, I = HMAC-SHA512("Bitcoin seed", seed)
+    pub fn new_master(seed: &[u8], network: Network) -> Result<ExtendedPrivateKey, Bip32Error> {
+        if seed.len() < 16 || seed.len() > 64 {
+            return Err(Bip32Error::InvalidSeedLength);
+        }
+        let (secret_key, chain_code) = hmac_split(b"Bitcoin seed", seed)?;
+        if secret_key.is_zero() {
+            return Err(Bip32Error::InvalidChild);
+        }
+        Ok(ExtendedPrivateKey {
+            network,
+            depth: 0,
+            parent_fingerprint: [0; 4],
+            child_number: 0,
+            chain_code,
+            secret_key,
+        })
+    }
+
+    /// CKDpriv, k_i = I_L + k_par
+    pub fn derive_child(&self, index: u32) -> Result<ExtendedPrivateKey, Bip32Error> {
+        let depth = self.depth.checked_add(1).ok_or(Bip32Error::DepthOverflow)?;
+        let data = if index >= HARDENED {
+            [&[0][..], &self.secret_key.to_be_bytes(), &index.to_be_bytes()].concat()
+        } else {
+            [&serialize_point(&self.point())[..], &index.to_be_bytes()].concat()
+        };
+        let (il, chain_code) = hmac_split(&self.chain_code, &data)?;
+        let secret_key = il.add(&self.secret_key);
+        if secret_key.is_zero() {
+            return Err(Bip32Error::InvalidChild);
+        }
+        Ok(ExtendedPrivateKey {
+            network: self.network,
+            depth,
+            parent_fingerprint: self.fingerprint(),
+            child_number: index,
+            chain_code,
+            secret_key,
+        })
+    }
+
+    /// key at a path relative to this key, e.g. "m/44'/0'/0'/0/1" from the master key
+    pub fn derive_path(&self, path: &str) -> Result<ExtendedPrivateKey, Bip32Error> {
+        parse_path(path)?.iter().try_fold(self.clone(), |key, index| key.derive_child(*index))
+    }
+
+    fn point(&self) -> Secp256k1Point {
+        normalize(&Secp256k1Point::generator().multiply(&self.secret_key))
+    }
+
+    /// N((k, c)), the extended public key with the same chain code
+    pub fn public_key(&self) -> ExtendedPublicKey {
+        ExtendedPublicKey {
+            network: self.network,
+            depth: self.depth,
+            parent_fingerprint: self.parent_fingerprint,
+            child_number: self.child_number,
+            chain_code: self.chain_code,
+            public_key: self.point(),
+        }
+    }
+
+    /// HASH160 of the compressed public key
+    pub fn identifier(&self) -> [u8; 20] {
+        hash160(&serialize_point(&self.point()))
+    }
+
+    pub fn fingerprint(&self) -> [u8; 4] {
+        let mut fingerprint = [0u8; 4];
+        fingerprint.copy_from_slice(&self.identifier()[..4]);
+        fingerprint
+    }
+
+    /// xprv / tprv
+    pub fn to_base58(&self) -> String {
+        let mut key = [0u8; 33];
+        key[1..].copy_from_slice(&self.secret_key.to_be_bytes());
+        serialize(self.network, true, self.depth, &self.parent_fingerprint, self.child_number, &self.chain_code, &key)
+    }
+
+    pub fn from_base58(s: &str) -> Result<ExtendedPrivateKey, Bip32Error> {
+        let serialized = deserialize(s)?;
+        if !serialized.private || matches!(serialized.key[0], 2 | 3) {
+            return Err(Bip32Error::InvalidVersion);
+        }
+        if serialized.key[0] != 0 {
+            return Err(Bip32Error::InvalidKey);
+        }
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&serialized.key[1..]);
+        let secret_key = match Scalar::from_be_bytes(&bytes) {
+            Some(k) if !k.is_zero() => k,
+            _ => return Err(Bip32Error::InvalidKey),
+        };
+        Ok(ExtendedPrivateKey {
+            network: serialized.network,
+            depth: serialized.depth,
+            parent_fingerprint: serialized.parent_fingerprint,
+            child_number: serialized.child_number,
+            chain_code: serialized.chain_code,
+            secret_key,
+        })
+    }
+}
+
+impl ExtendedPublicKey {
+    /// CKDpub, K_i = I_L G + K_par, normal children only
+    pub fn derive_child(&self, index: u32) -> Result<ExtendedPublicKey, Bip32Error> {
+        if index >= HARDENED {
+            return Err(Bip32Error::HardenedFromPublic);
+        }
+        let depth = self.depth.checked_add(1).ok_or(Bip32Error::DepthOverflow)?;
+        let data = [&serialize_point(&self.public_key)[..], &index.to_be_bytes()].concat();
+        let (il, chain_code) = hmac_split(&self.chain_code, &data)?;
+        let public_key = Secp256k1Point::generator().multiply(&il).add(&self.public_key);
+        if public_key.is_infinity() {
+            return Err(Bip32Error::InvalidChild);
+        }
+        Ok(ExtendedPublicKey {
+            network: self.network,
+            depth,
+            parent_fingerprint: self.fingerprint(),
+            child_number: index,
+            chain_code,
+            public_key: normalize(&public_key),
+        })
+    }
+
+    /// key at a path of normal child numbers relative to this key, e.g. "m/0/1"
+    pub fn derive_path(&self, path: &str) -> Result<ExtendedPublicKey, Bip32Error> {
+        parse_path(path)?.iter().try_fold(self.clone(), |key, index| key.derive_child(*index))
+    }
+
+    /// HASH160 of the compressed public key
+    pub fn identifier(&self) -> [u8; 20] {
+        hash160(&serialize_point(&self.public_key))
+    }
+
+    pub fn fingerprint(&self) -> [u8; 4] {
+        let mut fingerprint = [0u8; 4];
+        fingerprint.copy_from_slice(&self.identifier()[..4]);
+        fingerprint
+    }
+
+    /// xpub / tpub
+    pub fn to_base58(&self) -> String {
+        let key = serialize_point(&self.public_key);
+        serialize(self.network, false, self.depth, &self.parent_fingerprint, self.child_number, &self.chain_code, &key)
+    }
+
+    pub fn from_base58(s: &str) -> Result<ExtendedPublicKey, Bip32Error> {
+        let serialized = deserialize(s)?;
+        if serialized.private || serialized.key[0] == 0 {
+            return Err(Bip32Error::InvalidVersion);
+        }
+        let public_key = parse_point(&serialized.key).ok_or(Bip32Error::InvalidKey)?;
+        Ok(ExtendedPublicKey {
+            network: serialized.network,
+            depth: serialized.depth,
+            parent_fingerprint: serialized.parent_fingerprint,
+            child_number: serialized.child_number,
+            chain_code: serialized.chain_code,
+            public_key,
+        })
+    }
+}
+
+impl fmt::Display for ExtendedPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_base58())
+    }
+}
+
+impl fmt::Display for ExtendedPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_base58())
+    }
+}
+
+#[test]
+fn bip32_test_vectors() {
+    use super::hash::from_hex;
+    let csv = include_str!("../testdata/bip32_test_vectors.csv");
+    for line in csv.lines().skip(1) {
+        let fields: Vec<&str> = line.split(',').collect();
+        let (seed, path, xpub, xprv) = (fields[0], fields[1], fields[2], fields[3]);
+        let master = ExtendedPrivateKey::new_master(&from_hex(seed).unwrap(), Network::Mainnet).unwrap();
+        let key = master.derive_path(path).unwrap();
+        assert_eq_str!(key, xprv);
+        assert_eq_str!(key.public_key(), xpub);
+        assert_eq!(ExtendedPrivateKey::from_base58(xprv), Ok(key.clone()));
+        assert_eq!(ExtendedPublicKey::from_base58(xpub), Ok(key.public_key()));
+    }
+}
+
+#[test]
+fn bip32_invalid_keys_test() {
+    use super::base58::Base58Error;
+    // test vector 5
+    let vectors = [
+        // pubkey version / prvkey mismatch
+        ("xpub661MyMwAqRbcEYS8w7XLSVeEsBXy79zSzH1J8vCdxAZningWLdN3zgtU6LBpB85b3D2yc8sfvZU521AAwdZafEz7mnzBBsz4wKY5fTtTQBm", Bip32Error::InvalidVersion),
+        // prvkey version / pubkey mismatch
+        ("xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzFGTQQD3dC4H2D5GBj7vWvSQaaBv5cxi9gafk7NF3pnBju6dwKvH", Bip32Error::InvalidVersion),
+        // invalid pubkey prefix 04
+        ("xpub661MyMwAqRbcEYS8w7XLSVeEsBXy79zSzH1J8vCdxAZningWLdN3zgtU6Txnt3siSujt9RCVYsx4qHZGc62TG4McvMGcAUjeuwZdduYEvFn", Bip32Error::InvalidKey),
+        // invalid prvkey prefix 04
+        ("xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzFGpWnsj83BHtEy5Zt8CcDr1UiRXuWCmTQLxEK9vbz5gPstX92JQ", Bip32Error::InvalidKey),
+        // invalid pubkey prefix 01
+        ("xpub661MyMwAqRbcEYS8w7XLSVeEsBXy79zSzH1J8vCdxAZningWLdN3zgtU6N8ZMMXctdiCjxTNq964yKkwrkBJJwpzZS4HS2fxvyYUA4q2Xe4", Bip32Error::InvalidKey),
+        // invalid prvkey prefix 01
+        ("xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzFAzHGBP2UuGCqWLTAPLcMtD9y5gkZ6Eq3Rjuahrv17fEQ3Qen6J", Bip32Error::InvalidKey),
+        // zero depth with non-zero parent fingerprint
+        ("xprv9s2SPatNQ9Vc6GTbVMFPFo7jsaZySyzk7L8n2uqKXJen3KUmvQNTuLh3fhZMBoG3G4ZW1N2kZuHEPY53qmbZzCHshoQnNf4GvELZfqTUrcv", Bip32Error::InvalidKey),
+        ("xpub661no6RGEX3uJkY4bNnPcw4URcQTrSibUZ4NqJEw5eBkv7ovTwgiT91XX27VbEXGENhYRCf7hyEbWrR3FewATdCEebj6znwMfQkhRYHRLpJ", Bip32Error::InvalidKey),
+        // zero depth with non-zero index
+        ("xprv9s21ZrQH4r4TsiLvyLXqM9P7k1K3EYhA1kkD6xuquB5i39AU8KF42acDyL3qsDbU9NmZn6MsGSUYZEsuoePmjzsB3eFKSUEh3Gu1N3cqVUN", Bip32Error::InvalidKey),
+        ("xpub661MyMwAuDcm6CRQ5N4qiHKrJ39Xe1R1NyfouMKTTWcguwVcfrZJaNvhpebzGerh7gucBvzEQWRugZDuDXjNDRmXzSZe4c7mnTK97pTvGS8", Bip32Error::InvalidKey),
+        // unknown extended key version
+        ("DMwo58pR1QLEFihHiXPVykYB6fJmsTeHvyTp7hRThAtCX8CvYzgPcn8XnmdfHGMQzT7ayAmfo4z3gY5KfbrZWZ6St24UVf2Qgo6oujFktLHdHY4", Bip32Error::InvalidVersion),
+        ("DMwo58pR1QLEFihHiXPVykYB6fJmsTeHvyTp7hRThAtCX8CvYzgPcn8XnmdfHPmHJiEDXkTiJTVV9rHEBUem2mwVbbNfvT2MTcAqj3nesx8uBf9", Bip32Error::InvalidVersion),
+        // private key 0 not in 1..n-1
+        ("xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzF93Y5wvzdUayhgkkFoicQZcP3y52uPPxFnfoLZB21Teqt1VvEHx", Bip32Error::InvalidKey),
+        // private key n not in 1..n-1
+        ("xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzFAzHGBP2UuGCqWLTAPLcMtD5SDKr24z3aiUvKr9bJpdrcLg1y3G", Bip32Error::InvalidKey),
+        // invalid pubkey 020000000000000000000000000000000000000000000000000000000000000007
+        ("xpub661MyMwAqRbcEYS8w7XLSVeEsBXy79zSzH1J8vCdxAZningWLdN3zgtU6Q5JXayek4PRsn35jii4veMimro1xefsM58PgBMrvdYre8QyULY", Bip32Error::InvalidKey),
+        // invalid checksum
+        ("xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHL", Bip32Error::Base58(Base58Error::InvalidChecksum)),
+    ];
+    for (s, error) in vectors.iter() {
+        if s.starts_with("xpub") {
+            assert_eq!(ExtendedPublicKey::from_base58(s), Err(*error), "{}", s);
+        } else if s.starts_with("xprv") {
+            assert_eq!(ExtendedPrivateKey::from_base58(s), Err(*error), "{}", s);
+        } else {
+            assert_eq!(ExtendedPublicKey::from_base58(s), Err(*error), "{}", s);
+            assert_eq!(ExtendedPrivateKey::from_base58(s), Err(*error), "{}", s);
+        }
+    }
+}
+
+#[test]
+fn bip32_test() {
+    let seed: Vec<u8> = (0..16).collect();
+    let master = ExtendedPrivateKey::new_master(&seed, Network::Mainnet).unwrap();
+    assert_eq!(parse_path("m/44'/0h/0H/0/1"), Ok(vec![44 | HARDENED, HARDENED, HARDENED, 0, 1]));
+    assert_eq!(parse_path("m"), Ok(vec![]));
+    for path in ["", "M/0", "m/", "m/-1", "m/2147483648", "m/1'h", "m/+1"].iter() {
+        assert_eq!(parse_path(path), Err(Bip32Error::InvalidPath), "{}", path);
+    }
+
+    // public derivation of normal children matches private derivation
+    let account = master.derive_path("m/44'/0'/0'").unwrap();
+    let xpub = account.public_key();
+    let child = account.derive_path("m/0/1").unwrap();
+    assert_eq!(xpub.derive_path("m/0/1"), Ok(child.public_key()));
+    assert_eq!(child.parent_fingerprint, account.derive_child(0).unwrap().fingerprint());
+    assert_eq!(child.depth, 5);
+    assert_eq!(xpub.derive_child(HARDENED), Err(Bip32Error::HardenedFromPublic));
+
+    let testnet = ExtendedPrivateKey { network: Network::Testnet, ..child.clone() };
+    assert!(testnet.to_base58().starts_with("tprv"));
+    assert!(testnet.public_key().to_base58().starts_with("tpub"));
+    assert_eq!(ExtendedPrivateKey::from_base58(&testnet.to_base58()), Ok(testnet.clone()));
+    assert_eq!(ExtendedPrivateKey::from_base58(&xpub.to_base58()), Err(Bip32Error::InvalidVersion));
+    assert_eq!(ExtendedPublicKey::from_base58(&child.to_base58()), Err(Bip32Error::InvalidVersion));
+    assert_eq!(ExtendedPrivateKey::new_master(&[0; 15], Network::Mainnet), Err(Bip32Error::InvalidSeedLength));
+
+    // a master key with a parent fingerprint
+    let orphan = ExtendedPrivateKey { depth: 0, ..child.clone() };
+    assert_eq!(ExtendedPrivateKey::from_base58(&orphan.to_base58()), Err(Bip32Error::InvalidKey));
+    // public key data with an invalid prefix
+    let mut data = base58::decode_check(&xpub.to_base58()).unwrap();
+    data[45] = 4;
+    assert_eq!(ExtendedPublicKey::from_base58(&base58::encode_check(&data)), Err(Bip32Error::InvalidKey));
+    assert_eq!(ExtendedPublicKey::from_base58(&base58::encode_check(&data[..77])), Err(Bip32Error::InvalidLength));
+}
@@ -0,0 +1,221 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Zero, One};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use crate::bigint::{chinese_remainder, ModResult};
+
+/// Group law of a curve model
+/// algorithms written against this trait run on every model
+pub trait Curve {
+    type Point: Clone + PartialEq + fmt::Debug;
+
+    /// neutral element
+    fn identity(&self) -> Self::Point;
+
+    fn is_on_curve(&self, point: &Self::Point) -> bool;
+
+    /// -P
+    fn negate(&self, point: &Self::Point) -> Self::Point;
+
+    /// P + Q
+    fn plus(&self, point1: &Self::Point, point2: &Self::Point) -> Self::Point;
+
+    /// unique representative of P, used for comparison and hashing
+    fn normalize(&self, point: &Self::Point) -> Self::Point {
+        point.clone()
+    }
+
+    fn is_equal(&self, point1: &Self::Point, point2: &Self::Point) -> bool {
+        self.normalize(point1) == self.normalize(point2)
+    }
+
+    fn is_identity(&self, point: &Self::Point) -> bool {
+        self.is_equal(point, &self.identity())
+    }
+
+    /// P - Q
+    fn minus(&self, point1: &Self::Point, point2: &Self::Point) -> Self::Point {
+        self.plus(point1, &self.negate(point2))
+    }
+
+    /// n * P
+    fn multiply_scalar(&self, point: &Self::Point, n: &BigInt) -> Self::Point {
+        if n < &Zero::zero() {
+            return self.negate(&self.multiply_scalar(point, &(-n)));
+        }
+        let mut r = self.identity();
+        for i in (0..n.bits()).rev() {
+            r = self.plus(&r, &r);
+            if (n >> i).is_odd() {
+                r = self.plus(&r, point);
+            }
+        }
+        r
+    }
+
+    /// n1 * P1 + n2 * P2 + ... + nk * Pk
+    /// interleaved double and add, the doublings are shared
+    fn multi_scalar_multiply(&self, points: &[Self::Point], scalars: &[BigInt]) -> Self::Point {
+        assert_eq!(points.len(), scalars.len());
+        let terms: Vec<(Self::Point, BigInt)> = points.iter().zip(scalars).map(|(point, n)| {
+            if n < &Zero::zero() {
+                (self.negate(point), -n)
+            } else {
+                (point.clone(), n.clone())
+            }
+        }).collect();
+        let bits = terms.iter().map(|(_, n)| n.bits()).max().unwrap_or(0);
+        let mut r = self.identity();
+        for i in (0..bits).rev() {
+            r = self.plus(&r, &r);
+            for (point, n) in &terms {
+                if (n >> i).is_odd() {
+                    r = self.plus(&r, point);
+                }
+            }
+        }
+        r
+    }
+
+    /// order of P by repeated addition
+    fn point_order(&self, point: &Self::Point) -> BigInt {
+        let mut n: BigInt = One::one();
+        let mut r = point.clone();
+        while !self.is_identity(&r) {
+            r = self.plus(&r, point);
+            n += 1;
+        }
+        n
+    }
+
+    /// order of P, given a multiple n of it
+    fn point_order_dividing(&self, point: &Self::Point, n: &BigInt) -> BigInt {
+        assert!(self.is_identity(&self.multiply_scalar(point, n)), "n is not a multiple of the order");
+        let mut order = n.clone();
+        for (q, _) in factorize(n) {
+            while order.is_multiple_of(&q) && self.is_identity(&self.multiply_scalar(point, &(&order / &q))) {
+                order /= &q;
+            }
+        }
+        order
+    }
+
+    /// Baby-step giant-step
+    /// k with k * base = point, 0 <= k < order
+    fn discrete_log(&self, point: &Self::Point, base: &Self::Point, order: &BigInt) -> Option<BigInt>
+    where Self::Point: Eq + Hash {
+        let m = order.sqrt() + BigInt::from(1);
+        let mut table: HashMap<Self::Point, BigInt> = HashMap::new();
+        let mut r = self.normalize(&self.identity());
+        for j in num_iter::range(BigInt::zero(), m.clone()) {
+            table.entry(r.clone()).or_insert(j);
+            r = self.normalize(&self.plus(&r, base));
+        }
+        let giant = self.negate(&self.multiply_scalar(base, &m));
+        let mut r = self.normalize(point);
+        for i in num_iter::range(BigInt::zero(), m.clone()) {
+            if let Some(j) = table.get(&r) {
+                return Some((&i * &m + j).mod_floor(order));
+            }
+            r = self.normalize(&self.plus(&r, &giant));
+        }
+        None
+    }
+
+    /// Pohlig-Hellman
+    /// reduces the discrete log to the prime order subgroups and combines by CRT
+    fn pohlig_hellman(&self, point: &Self::Point, base: &Self::Point, order: &BigInt) -> Option<BigInt>
+    where Self::Point: Eq + Hash {
+        let mut mod_result: Vec<ModResult> = Vec::new();
+        for (q, e) in factorize(order) {
+            let qe = num_traits::pow(q.clone(), e);
+            // base of order q
+            let gamma = self.multiply_scalar(base, &(order / &q));
+            let mut x: BigInt = Zero::zero();
+            let mut qk: BigInt = One::one();
+            for _ in 0..e {
+                // h = (order / q^(k+1)) * (point - x * base)
+                let h = self.minus(point, &self.multiply_scalar(base, &x));
+                let h = self.multiply_scalar(&h, &(order / (&qk * &q)));
+                let d = self.discrete_log(&h, &gamma, &q)?;
+                x += &d * &qk;
+                qk *= &q;
+            }
+            mod_result.push(ModResult { l: qe, r: x });
+        }
+        let result = chinese_remainder(&mod_result);
+        let k = result.r.mod_floor(&result.l);
+        if self.is_equal(&self.multiply_scalar(base, &k), point) {
+            Some(k)
+        } else {
+            None
+        }
+    }
+}
+
+/// prime factorization by trial division
+/// [(q, e)] with n = prod q^e
+pub fn factorize(n: &BigInt) -> Vec<(BigInt, usize)> {
+    let mut factors: Vec<(BigInt, usize)> = Vec::new();
+    let mut n = n.clone();
+    let mut q = BigInt::from(2);
+    while &q * &q <= n {
+        let mut e = 0;
+        while n.is_multiple_of(&q) {
+            n /= &q;
+            e += 1;
+        }
+        if e > 0 {
+            factors.push((q.clone(), e));
+        }
+        q += 1;
+    }
+    if n > One::one() {
+        factors.push((n, 1));
+    }
+    factors
+}
+
+#[test]
+fn factorize_test() {
+    let factors = factorize(&BigInt::from(360));
+    assert_eq!(factors, vec![(BigInt::from(2), 3), (BigInt::from(3), 2), (BigInt::from(5), 1)]);
+    assert_eq!(factorize(&BigInt::from(97)), vec![(BigInt::from(97), 1)]);
+}
+
+#[cfg(test)]
+fn check_discrete_log<C: Curve>(curve: &C, points: &[C::Point])
+where C::Point: Eq + Hash {
+    // point of maximal order as the base
+    let g = points.iter().max_by_key(|point| curve.point_order(point)).unwrap();
+    let n = curve.point_order(g);
+    assert_eq!(curve.point_order_dividing(g, &BigInt::from(points.len())), n);
+    for k in num_iter::range(BigInt::zero(), n.clone()) {
+        let q = curve.multiply_scalar(g, &k);
+        assert_eq!(curve.discrete_log(&q, g, &n), Some(k.clone()));
+        assert_eq!(curve.pohlig_hellman(&q, g, &n), Some(k.clone()));
+    }
+    let scalars = [BigInt::from(5), BigInt::from(-7), BigInt::from(11)];
+    let bases = [g.clone(), curve.plus(g, g), curve.identity()];
+    let r = curve.multi_scalar_multiply(&bases, &scalars);
+    assert!(curve.is_equal(&r, &curve.multiply_scalar(g, &BigInt::from(-9))));
+}
+
+#[test]
+fn curve_test() {
+    use crate::elliptic_curve::EllipticCurve;
+    use crate::montgomery_curve::MontgomeryCurve;
+    use crate::twisted_edwards_curve::TwistedEdwardsCurve;
+    use crate::hessian_curve::HessianCurve;
+
+    let ec = EllipticCurve::new(&BigInt::from(2), &BigInt::from(3), &BigInt::from(97));
+    check_discrete_log(&ec, &ec.points());
+    let curve = MontgomeryCurve::new(&BigInt::from(3), &BigInt::from(5), &BigInt::from(101));
+    check_discrete_log(&curve, &curve.points());
+    let curve = TwistedEdwardsCurve::new(&BigInt::from(1), &BigInt::from(2), &BigInt::from(13));
+    check_discrete_log(&curve, &curve.points());
+    let curve = HessianCurve::new(&BigInt::from(2), &BigInt::from(31));
+    check_discrete_log(&curve, &curve.points());
+}
@@ -0,0 +1,199 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use std::fmt;
+use crate::bigint::is_probable_prime;
+use super::elliptic_curve::{EllipticCurve, ECPoint};
+use super::named_curve;
+
+/// MOV condition bound B of SEC 1
+const MOV_BOUND: u32 = 100;
+
+/// Elliptic curve domain parameters over GF(p), SEC 1 3.1.1
+/// y^2 = x^3 + a x + b, generator g of prime order n, cofactor h
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DomainParameters {
+    pub name: &'static str,
+    pub oid: &'static str,
+    pub p: BigInt,
+    pub a: BigInt,
+    pub b: BigInt,
+    pub g: ECPoint,
+    pub n: BigInt,
+    pub h: BigInt,
+}
+
+/// reason the domain parameters are rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationError {
+    /// p is not an odd prime
+    FieldNotPrime,
+    /// a, b, or the coordinates of G are not in [0, p)
+    OutOfRange,
+    /// 4 a^3 + 27 b^2 = 0 (mod p)
+    Singular,
+    GeneratorNotOnCurve,
+    OrderNotPrime,
+    /// n G != O
+    WrongOrder,
+    /// n too small or h inconsistent with the Hasse bound
+    WrongCofactor,
+    /// p^B = 1 (mod n) for some B <= 100
+    Mov,
+    /// n = p
+    Anomalous,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            ValidationError::FieldNotPrime => "p is not an odd prime",
+            ValidationError::OutOfRange => "parameter out of range",
+            ValidationError::Singular => "curve is singular",
+            ValidationError::GeneratorNotOnCurve => "generator is not on curve",
+            ValidationError::OrderNotPrime => "n is not prime",
+            ValidationError::WrongOrder => "n G is not infinity",
+            ValidationError::WrongCofactor => "cofactor is not consistent with the Hasse bound",
+            ValidationError::Mov => "curve is vulnerable to the MOV attack",
+            ValidationError::Anomalous => "curve is anomalous",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl DomainParameters {
+    /// from big-endian hex strings
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_hex(name: &'static str, oid: &'static str,
+                    p: &str, a: &str, b: &str, gx: &str, gy: &str, n: &str, h: u64) -> DomainParameters {
+        let hex = |s: &str| BigInt::parse_bytes(s.as_bytes(), 16).expect("invalid hex");
+        DomainParameters {
+            name,
+            oid,
+            p: hex(p),
+            a: hex(a),
+            b: hex(b),
+            g: ECPoint::new(&hex(gx), &hex(gy), &One::one()),
+            n: hex(n),
+            h: BigInt::from(h),
+        }
+    }
+
+    /// SEC 2 secp256k1
+    pub fn secp256k1() -> DomainParameters {
+        named_curve::by_name("secp256k1").unwrap()
+    }
+
+    /// the curve, without the small-prime point enumeration of EllipticCurve::new
+    pub fn curve(&self) -> EllipticCurve {
+        EllipticCurve::new_raw(&self.a, &self.b, &self.p)
+    }
+
+    /// SEC 1 3.1.1.2.1
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let (p, a, b, n, h) = (&self.p, &self.a, &self.b, &self.n, &self.h);
+        if p.is_even() || !is_probable_prime(p) {
+            return Err(ValidationError::FieldNotPrime);
+        }
+        let in_range = |v: &BigInt| v >= &Zero::zero() && v < p;
+        let in_range = in_range(a) && in_range(b) && in_range(&self.g.x) && in_range(&self.g.y);
+        if !in_range || !self.g.z.is_one() {
+            return Err(ValidationError::OutOfRange);
+        }
+        let disc = (BigInt::from(4) * a * a * a + BigInt::from(27) * b * b).mod_floor(p);
+        if disc.is_zero() {
+            return Err(ValidationError::Singular);
+        }
+        let ec = self.curve();
+        if !ec.is_on_curve(&self.g) {
+            return Err(ValidationError::GeneratorNotOnCurve);
+        }
+        if !is_probable_prime(n) {
+            return Err(ValidationError::OrderNotPrime);
+        }
+        // n > 4 sqrt(p), h = floor((sqrt(p) + 1)^2 / n)
+        if n * n <= BigInt::from(16) * p {
+            return Err(ValidationError::WrongCofactor);
+        }
+        let hasse = (p + BigInt::one() + (BigInt::from(4) * p).sqrt()) / n;
+        if h != &hasse {
+            return Err(ValidationError::WrongCofactor);
+        }
+        if !ec.multiply_scalar(&self.g, n).is_infinity() {
+            return Err(ValidationError::WrongOrder);
+        }
+        let mut q: BigInt = One::one();
+        for _ in 0..MOV_BOUND {
+            q = (q * p).mod_floor(n);
+            if q.is_one() {
+                return Err(ValidationError::Mov);
+            }
+        }
+        if n == p {
+            return Err(ValidationError::Anomalous);
+        }
+        Ok(())
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+    }
+}
+
+impl fmt::Display for DomainParameters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({}): y^2 = x^3 + {} x + {} (mod {}), G = {}, n = {}, h = {}",
+               self.name, self.oid, self.a, self.b, self.p, self.g, self.n, self.h)
+    }
+}
+
+#[test]
+fn domain_parameters_test() {
+    let params = DomainParameters::secp256k1();
+    assert_eq!(params.validate(), Ok(()));
+
+    let mut bad = params.clone();
+    bad.p = &params.p + 2;
+    assert_eq!(bad.validate(), Err(ValidationError::FieldNotPrime));
+
+    let mut bad = params.clone();
+    bad.g.x = &params.g.x + 1;
+    assert_eq!(bad.validate(), Err(ValidationError::GeneratorNotOnCurve));
+
+    let mut bad = params.clone();
+    bad.b = Zero::zero();
+    bad.g = ECPoint::new(&Zero::zero(), &Zero::zero(), &One::one());
+    assert_eq!(bad.validate(), Err(ValidationError::Singular));
+
+    let mut bad = params.clone();
+    bad.n = &params.n + 2;
+    assert_eq!(bad.validate(), Err(ValidationError::OrderNotPrime));
+
+    let mut bad = params.clone();
+    bad.h = BigInt::from(2);
+    assert_eq!(bad.validate(), Err(ValidationError::WrongCofactor));
+
+    let mut bad = params.clone();
+    bad.n = &params.n + 2;
+    while !is_probable_prime(&bad.n) {
+        bad.n += 2;
+    }
+    assert_eq!(bad.validate(), Err(ValidationError::WrongOrder));
+
+    let small = |p: u32, a: u32, b: u32, gx: u32, gy: u32, n: u32, h: u32| DomainParameters {
+        name: "small",
+        oid: "",
+        p: BigInt::from(p),
+        a: BigInt::from(a),
+        b: BigInt::from(b),
+        g: ECPoint::new(&BigInt::from(gx), &BigInt::from(gy), &One::one()),
+        n: BigInt::from(n),
+        h: BigInt::from(h),
+    };
+    // supersingular y^2 = x^3 + x over GF(283) has 284 = 4 * 71 points, 283^2 = 1 (mod 71)
+    assert_eq!(small(283, 1, 0, 159, 39, 71, 4).validate(), Err(ValidationError::Mov));
+    // y^2 = x^3 + 4 x + 3 over GF(211) has 211 points
+    assert_eq!(small(211, 4, 3, 2, 21, 211, 1).validate(), Err(ValidationError::Anomalous));
+    // y^2 = x^3 + 2 x + 3 over GF(97), (3, 6) of order 5 is too small
+    assert_eq!(small(97, 2, 3, 3, 6, 5, 20).validate(), Err(ValidationError::WrongCofactor));
+}
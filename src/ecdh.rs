@@ -0,0 +1,212 @@
+use num_bigint::{BigInt, Sign};
+use num_traits::Zero;
+use rand::Rng;
+use std::fmt;
+use super::domain_parameters::DomainParameters;
+use super::elliptic_curve::{EllipticCurve, ECPoint};
+use super::hash::hkdf_sha256;
+
+/// SEC 1 3.3.1 standard or 3.3.2 cofactor Diffie-Hellman primitive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EcdhMode {
+    /// P = d Q
+    Standard,
+    /// P = h d Q, points of small order give infinity
+    Cofactor,
+}
+
+/// reason a key agreement is rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EcdhError {
+    InvalidPrivateKey,
+    PointAtInfinity,
+    NotOnCurve,
+    /// n Q != O
+    NotInSubgroup,
+    /// the shared point is infinity
+    SharedSecretIsInfinity,
+}
+
+impl fmt::Display for EcdhError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            EcdhError::InvalidPrivateKey => "private key out of range",
+            EcdhError::PointAtInfinity => "public key is infinity",
+            EcdhError::NotOnCurve => "public key is not on curve",
+            EcdhError::NotInSubgroup => "public key is not in the subgroup generated by G",
+            EcdhError::SharedSecretIsInfinity => "shared point is infinity",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Elliptic curve Diffie-Hellman over the curve of the domain parameters
+#[derive(Debug, Clone)]
+pub struct Ecdh {
+    pub params: DomainParameters,
+    pub mode: EcdhMode,
+    /// reject public keys outside <G>, costs one scalar multiplication
+    pub subgroup_check: bool,
+    ec: EllipticCurve,
+}
+
+impl Ecdh {
+    pub fn new(params: &DomainParameters, mode: EcdhMode) -> Ecdh {
+        Ecdh {
+            params: params.clone(),
+            mode,
+            subgroup_check: false,
+            ec: params.curve(),
+        }
+    }
+
+    /// Q = d G
+    pub fn public_key(&self, d: &BigInt) -> ECPoint {
+        assert!(d > &Zero::zero() && d < &self.params.n, "private key out of range");
+        self.ec.multiply_scalar(&self.params.g, d)
+    }
+
+    /// (d, Q) with d uniform in [1, n)
+    pub fn generate_key<R: Rng + ?Sized>(&self, rng: &mut R) -> (BigInt, ECPoint) {
+        let n = &self.params.n;
+        let len = n.bits().div_ceil(8);
+        loop {
+            let bytes: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            let d = BigInt::from_bytes_be(Sign::Plus, &bytes) >> (8 * len - n.bits());
+            if !d.is_zero() && &d < n {
+                let q = self.public_key(&d);
+                return (d, q);
+            }
+        }
+    }
+
+    /// public key validation, SEC 1 3.2.2.1 with the subgroup check optional
+    pub fn validate_public_key(&self, q: &ECPoint) -> Result<(), EcdhError> {
+        if q.is_infinity() {
+            return Err(EcdhError::PointAtInfinity);
+        }
+        let p = &self.params.p;
+        let in_range = |v: &BigInt| v >= &Zero::zero() && v < p;
+        if !in_range(&q.x) || !in_range(&q.y) || !self.ec.is_on_curve(q) {
+            return Err(EcdhError::NotOnCurve);
+        }
+        if self.subgroup_check && !self.ec.multiply_scalar(q, &self.params.n).is_infinity() {
+            return Err(EcdhError::NotInSubgroup);
+        }
+        Ok(())
+    }
+
+    /// x coordinate of the shared point as ceil(bits(p) / 8) big-endian bytes
+    pub fn shared_secret(&self, d: &BigInt, q: &ECPoint) -> Result<Vec<u8>, EcdhError> {
+        if d <= &Zero::zero() || d >= &self.params.n {
+            return Err(EcdhError::InvalidPrivateKey);
+        }
+        self.validate_public_key(q)?;
+        let k = match self.mode {
+            EcdhMode::Standard => d.clone(),
+            EcdhMode::Cofactor => d * &self.params.h,
+        };
+        let point = self.ec.multiply_scalar(q, &k);
+        if point.is_infinity() {
+            return Err(EcdhError::SharedSecretIsInfinity);
+        }
+        let point = self.ec.canonicalize(&point);
+        let len = self.params.p.bits().div_ceil(8);
+        let (_, bytes) = point.x.to_bytes_be();
+        let mut z = vec![0u8; len - bytes.len()];
+        z.extend_from_slice(&bytes);
+        Ok(z)
+    }
+
+    /// HKDF-SHA-256 of the shared secret
+    pub fn derive_key(&self, d: &BigInt, q: &ECPoint, salt: &[u8], info: &[u8], length: usize)
+        -> Result<Vec<u8>, EcdhError> {
+        let z = self.shared_secret(d, q)?;
+        Ok(hkdf_sha256(salt, &z, info, length))
+    }
+
+    /// ephemeral-static agreement, returns the ephemeral public key to send and the derived key
+    pub fn ephemeral<R: Rng + ?Sized>(&self, rng: &mut R, q: &ECPoint, salt: &[u8], info: &[u8], length: usize)
+        -> Result<(ECPoint, Vec<u8>), EcdhError> {
+        self.validate_public_key(q)?;
+        let (d, ephemeral) = self.generate_key(rng);
+        let key = self.derive_key(&d, q, salt, info, length)?;
+        Ok((ephemeral, key))
+    }
+}
+
+#[test]
+fn ecdh_test() {
+    use rand::{SeedableRng, rngs::StdRng};
+    use super::hash::{from_hex, to_hex};
+    use super::named_curve;
+    let hex = |s: &str| BigInt::parse_bytes(s.as_bytes(), 16).unwrap();
+    // shared secrets computed with an independent implementation
+    let vectors = [
+        ("secp256r1",
+         "7d7dc5f71eb29ddaf80d6214632eeae03d9058af1fb6d22ed80badb62bc1a534",
+         "38f65d6dce47676044d58ce5139582d568f64bb16098d179dbab07741dd5caf5",
+         "119f2f047902782ab0c9e27a54aff5eb9b964829ca99c06b02ddba95b0a3f6d0",
+         "69854de86f85d63854b189cd4f7a556c668977ed93277edc449e9f7655b28175",
+         "a649d036d1017be5fc2e80eedf9143304cea9baf3d88e6087d296261707de0b9"),
+        ("secp256k1",
+         "1111111111111111111111111111111111111111111111111111111111111111",
+         "2222222222222222222222222222222222222222222222222222222222222222",
+         "466d7fcae563e5cb09a0d1870bb580344804617879a14949cf22285f1bae3f27",
+         "77e0510d5042e2f5e9e59c977b81eeed590cf7d20c1c51da451a8eaa9fdc45ff",
+         "3992802db232e4ab85354fe23c959faa4db1b655d0ada7b1f6d6a10f4adf4160"),
+    ];
+    for (name, da, db, qbx, z, okm) in vectors.iter() {
+        let ecdh = Ecdh::new(&named_curve::by_name(name).unwrap(), EcdhMode::Standard);
+        let (da, db) = (hex(da), hex(db));
+        let (qa, qb) = (ecdh.public_key(&da), ecdh.public_key(&db));
+        assert_eq!(qb.x, hex(qbx));
+        assert_eq!(to_hex(&ecdh.shared_secret(&da, &qb).unwrap()), *z);
+        assert_eq!(ecdh.shared_secret(&db, &qa), ecdh.shared_secret(&da, &qb));
+        assert_eq!(ecdh.derive_key(&da, &qb, b"salt", b"info", 32).unwrap(), from_hex(okm).unwrap());
+    }
+
+    let ecdh = Ecdh::new(&DomainParameters::secp256k1(), EcdhMode::Standard);
+    let mut rng = StdRng::seed_from_u64(46);
+    let (d, q) = ecdh.generate_key(&mut rng);
+    let (ephemeral, key) = ecdh.ephemeral(&mut rng, &q, &[], b"", 16).unwrap();
+    assert_eq!(ecdh.derive_key(&d, &ephemeral, &[], b"", 16).unwrap(), key);
+    assert_eq!(ecdh.shared_secret(&d, &ECPoint::infinity()), Err(EcdhError::PointAtInfinity));
+    let off_curve = ECPoint::new(&q.x, &(&q.y + 1), &q.z);
+    assert_eq!(ecdh.shared_secret(&d, &off_curve), Err(EcdhError::NotOnCurve));
+    assert_eq!(ecdh.shared_secret(&Zero::zero(), &q), Err(EcdhError::InvalidPrivateKey));
+}
+
+#[test]
+fn ecdh_cofactor_test() {
+    use num_traits::One;
+    // y^2 = x^3 + x over GF(283), 284 = 4 * 71 points, (0, 0) has order 2
+    let params = DomainParameters {
+        name: "small",
+        oid: "",
+        p: BigInt::from(283),
+        a: BigInt::from(1),
+        b: BigInt::from(0),
+        g: ECPoint::new(&BigInt::from(159), &BigInt::from(39), &One::one()),
+        n: BigInt::from(71),
+        h: BigInt::from(4),
+    };
+    let small_order = ECPoint::new(&Zero::zero(), &Zero::zero(), &One::one());
+    let d = BigInt::from(5);
+    let q = params.curve().multiply_scalar(&params.g, &BigInt::from(9));
+
+    let mut ecdh = Ecdh::new(&params, EcdhMode::Standard);
+    // d (0, 0) = (0, 0) reveals d mod 2
+    assert_eq!(ecdh.shared_secret(&d, &small_order), Ok(vec![0, 0]));
+    ecdh.subgroup_check = true;
+    assert_eq!(ecdh.shared_secret(&d, &small_order), Err(EcdhError::NotInSubgroup));
+    let standard = ecdh.shared_secret(&d, &q).unwrap();
+
+    let ecdh = Ecdh::new(&params, EcdhMode::Cofactor);
+    assert_eq!(ecdh.shared_secret(&d, &small_order), Err(EcdhError::SharedSecretIsInfinity));
+    let cofactor = ecdh.shared_secret(&d, &q).unwrap();
+    // h d Q = (4 d) Q
+    let expected = params.curve().multiply_scalar(&q, &BigInt::from(20));
+    assert_eq!(BigInt::from_bytes_be(Sign::Plus, &cofactor), expected.x);
+    assert_ne!(standard, cofactor);
+}
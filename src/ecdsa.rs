@@ -0,0 +1,323 @@
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use rand::Rng;
+use crate::bigint::inverse_mod;
+use super::curve::Curve;
+use super::domain_parameters::DomainParameters;
+use super::elliptic_curve::{EllipticCurve, ECPoint};
+use super::hash::{hmac_sha256, sha256};
+
+/// ECDSA signature (r, s), 0 < r, s < n
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Signature {
+    pub r: BigInt,
+    pub s: BigInt,
+}
+
+impl Signature {
+    pub fn new(r: &BigInt, s: &BigInt) -> Signature {
+        Signature { r: r.clone(), s: s.clone() }
+    }
+
+    /// s <= n / 2
+    pub fn is_low_s(&self, n: &BigInt) -> bool {
+        &self.s * 2 <= *n
+    }
+
+    /// (r, s) or (r, n - s), whichever has s <= n / 2
+    pub fn normalize_s(&self, n: &BigInt) -> Signature {
+        if self.is_low_s(n) {
+            self.clone()
+        } else {
+            Signature { r: self.r.clone(), s: n - &self.s }
+        }
+    }
+}
+
+/// ECDSA over any curve of known prime order n, SEC 1 4.1
+/// messages are hashed with SHA-256, nonces follow RFC 6979 with HMAC-SHA-256
+#[derive(Debug, Clone)]
+pub struct Ecdsa {
+    pub params: DomainParameters,
+    ec: EllipticCurve,
+}
+
+/// HMAC_DRBG state of RFC 6979 3.2
+struct NonceGenerator<'a> {
+    ecdsa: &'a Ecdsa,
+    k: [u8; 32],
+    v: [u8; 32],
+}
+
+impl<'a> NonceGenerator<'a> {
+    fn new(ecdsa: &'a Ecdsa, d: &BigInt, hash: &[u8]) -> NonceGenerator<'a> {
+        let x = ecdsa.int_to_octets(d);
+        let h = ecdsa.int_to_octets(&ecdsa.bits_to_int(hash).mod_floor(&ecdsa.params.n));
+        let mut gen = NonceGenerator { ecdsa, k: [0; 32], v: [1; 32] };
+        for byte in &[0u8, 1u8] {
+            let data = [&gen.v[..], &[*byte], &x, &h].concat();
+            gen.k = hmac_sha256(&gen.k, &data);
+            gen.v = hmac_sha256(&gen.k, &gen.v);
+        }
+        gen
+    }
+
+    /// next candidate k in [1, n)
+    fn next(&mut self) -> BigInt {
+        let n = &self.ecdsa.params.n;
+        loop {
+            let mut t: Vec<u8> = Vec::new();
+            while t.len() * 8 < n.bits() {
+                self.v = hmac_sha256(&self.k, &self.v);
+                t.extend_from_slice(&self.v);
+            }
+            let k = self.ecdsa.bits_to_int(&t);
+            self.k = hmac_sha256(&self.k, &[&self.v[..], &[0u8]].concat());
+            self.v = hmac_sha256(&self.k, &self.v);
+            if !k.is_zero() && &k < n {
+                return k;
+            }
+        }
+    }
+}
+
+impl Ecdsa {
+    pub fn new(params: &DomainParameters) -> Ecdsa {
+        Ecdsa {
+            params: params.clone(),
+            ec: params.curve(),
+        }
+    }
+
+    pub fn curve(&self) -> &EllipticCurve {
+        &self.ec
+    }
+
+    /// Q = d G
+    pub fn public_key(&self, d: &BigInt) -> ECPoint {
+        assert!(d > &Zero::zero() && d < &self.params.n, "private key out of range");
+        self.ec.multiply_scalar(&self.params.g, d)
+    }
+
+    /// (d, Q) with d uniform in [1, n)
+    pub fn generate_key<R: Rng + ?Sized>(&self, rng: &mut R) -> (BigInt, ECPoint) {
+        let n = &self.params.n;
+        let len = n.bits().div_ceil(8);
+        loop {
+            let bytes: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            let d = self.bits_to_int(&bytes);
+            if !d.is_zero() && &d < n {
+                let q = self.public_key(&d);
+                return (d, q);
+            }
+        }
+    }
+
+    /// leftmost bits(n) bits of the octet string, RFC 6979 2.3.2
+    pub(crate) fn bits_to_int(&self, bytes: &[u8]) -> BigInt {
+        let qlen = self.params.n.bits();
+        let x = BigInt::from_bytes_be(Sign::Plus, bytes);
+        if bytes.len() * 8 > qlen {
+            x >> (bytes.len() * 8 - qlen)
+        } else {
+            x
+        }
+    }
+
+    /// big-endian, ceil(bits(n) / 8) bytes, RFC 6979 2.3.3
+    fn int_to_octets(&self, x: &BigInt) -> Vec<u8> {
+        let len = self.params.n.bits().div_ceil(8);
+        let (_, bytes) = x.to_bytes_be();
+        let mut out = vec![0u8; len.saturating_sub(bytes.len())];
+        out.extend_from_slice(&bytes[bytes.len().saturating_sub(len)..]);
+        out
+    }
+
+    /// first RFC 6979 nonce for the private key d and the message hash
+    pub fn nonce(&self, d: &BigInt, hash: &[u8]) -> BigInt {
+        NonceGenerator::new(self, d, hash).next()
+    }
+
+    /// r = (k G).x mod n, s = (e + r d) / k mod n, normalized to low s
+    pub fn sign_hash(&self, d: &BigInt, hash: &[u8]) -> Signature {
+        self.sign_hash_recoverable(d, hash).0
+    }
+
+    /// signature and recovery id
+    /// bit 0 of the id is the parity of R.y, bit 1 is set when R.x >= n
+    pub fn sign_hash_recoverable(&self, d: &BigInt, hash: &[u8]) -> (Signature, u8) {
+        let n = &self.params.n;
+        let e = self.bits_to_int(hash);
+        let mut nonces = NonceGenerator::new(self, d, hash);
+        loop {
+            let k = nonces.next();
+            let point = self.ec.multiply_scalar(&self.params.g, &k);
+            let r = point.x.mod_floor(n);
+            if r.is_zero() {
+                continue;
+            }
+            let k_inv = inverse_mod(&k, n).unwrap();
+            let s = (k_inv * (&e + &r * d)).mod_floor(n);
+            if s.is_zero() {
+                continue;
+            }
+            let mut recovery_id = point.y.is_odd() as u8 | ((&point.x >= n) as u8) << 1;
+            let signature = Signature { r, s };
+            if !signature.is_low_s(n) {
+                // s -> n - s is the signature of -k, whose R has the other parity
+                recovery_id ^= 1;
+            }
+            return (signature.normalize_s(n), recovery_id);
+        }
+    }
+
+    /// Q = (s R - e G) / r with R lifted from x = r + (id >> 1) n and the parity id & 1
+    pub fn recover_public_key(&self, hash: &[u8], signature: &Signature, recovery_id: u8) -> Option<ECPoint> {
+        let n = &self.params.n;
+        let (r, s) = (&signature.r, &signature.s);
+        let in_range = |v: &BigInt| v > &Zero::zero() && v < n;
+        if recovery_id > 3 || !in_range(r) || !in_range(s) {
+            return None;
+        }
+        let x = if recovery_id & 2 != 0 { r + n } else { r.clone() };
+        if x >= self.params.p {
+            return None;
+        }
+        let point_r = self.ec.lift_x(&x, recovery_id & 1 == 1)?;
+        if !self.params.h.is_one() && !self.ec.multiply_scalar(&point_r, n).is_infinity() {
+            return None;
+        }
+        let e = self.bits_to_int(hash);
+        let r_inv = inverse_mod(r, n).unwrap();
+        let u1 = (s * &r_inv).mod_floor(n);
+        let u2 = (-(e * &r_inv)).mod_floor(n);
+        let q = self.ec.multi_scalar_multiply(&[point_r, self.params.g.clone()], &[u1, u2]);
+        if q.is_infinity() {
+            None
+        } else {
+            Some(self.ec.canonicalize(&q))
+        }
+    }
+
+    /// sign SHA-256(message)
+    pub fn sign(&self, d: &BigInt, message: &[u8]) -> Signature {
+        self.sign_hash(d, &sha256(message))
+    }
+
+    /// u1 = e / s, u2 = r / s, (u1 G + u2 Q).x = r (mod n)
+    /// both s and n - s are accepted
+    pub fn verify_hash(&self, q: &ECPoint, hash: &[u8], signature: &Signature) -> bool {
+        let n = &self.params.n;
+        let (r, s) = (&signature.r, &signature.s);
+        let in_range = |v: &BigInt| v > &Zero::zero() && v < n;
+        if !in_range(r) || !in_range(s) || q.is_infinity() || !self.ec.is_on_curve(q) {
+            return false;
+        }
+        let e = self.bits_to_int(hash);
+        let w = inverse_mod(s, n).unwrap();
+        let u1 = (&e * &w).mod_floor(n);
+        let u2 = (r * &w).mod_floor(n);
+        let point = self.ec.multi_scalar_multiply(&[self.params.g.clone(), q.clone()], &[u1, u2]);
+        if point.is_infinity() {
+            return false;
+        }
+        let point = self.ec.canonicalize(&point);
+        debug_assert!(point.z.is_one());
+        &point.x.mod_floor(n) == r
+    }
+
+    /// verify against SHA-256(message)
+    pub fn verify(&self, q: &ECPoint, message: &[u8], signature: &Signature) -> bool {
+        self.verify_hash(q, &sha256(message), signature)
+    }
+}
+
+#[cfg(test)]
+fn hex(s: &str) -> BigInt {
+    BigInt::parse_bytes(s.as_bytes(), 16).unwrap()
+}
+
+#[test]
+fn ecdsa_rfc6979_test() {
+    use super::named_curve;
+    // RFC 6979 A.2.5, P-256 with SHA-256
+    let ecdsa = Ecdsa::new(&named_curve::by_name("P-256").unwrap());
+    let n = &ecdsa.params.n;
+    let d = hex("C9AFA9D845BA75166B5C215767B1D6934E50C3DB36E89B127B8A622B120F6721");
+    let q = ecdsa.public_key(&d);
+    assert_eq!(q.x, hex("60FED4BA255A9D31C961EB74C6356D68C049B8923B61FA6CE669622E60F29FB6"));
+    assert_eq!(q.y, hex("7903FE1008B8BC99A41AE9E95628BC64F2F1B20C2D7E9F5177A3C294D4462299"));
+    let vectors = [
+        ("sample",
+         "A6E3C57DD01ABE90086538398355DD4C3B17AA873382B0F24D6129493D8AAD60",
+         "EFD48B2AACB6A8FD1140DD9CD45E81D69D2C877B56AAF991C34D0EA84EAF3716",
+         "F7CB1C942D657C41D436C7A1B6E29F65F3E900DBB9AFF4064DC4AB2F843ACDA8"),
+        ("test",
+         "D16B6AE827F17175E040871A1C7EC3500192C4C92677336EC2537ACAEE0008E0",
+         "F1ABB023518351CD71D881567B1EA663ED3EFCF6C5132B354F28D3B0B7D38367",
+         "019F4113742A2B14BD25926B49C649155F267E60D3814B4C0CC84250E46F0083"),
+    ];
+    for (message, k, r, s) in vectors.iter() {
+        assert_eq!(ecdsa.nonce(&d, &sha256(message.as_bytes())), hex(k));
+        let expected = Signature::new(&hex(r), &hex(s));
+        let signature = ecdsa.sign(&d, message.as_bytes());
+        assert_eq!(signature, expected.normalize_s(n));
+        assert!(ecdsa.verify(&q, message.as_bytes(), &signature));
+        assert!(ecdsa.verify(&q, message.as_bytes(), &expected));
+        assert!(!ecdsa.verify(&q, b"other", &signature));
+    }
+
+    // RFC 6979 A.2.3, P-192 with SHA-256, the hash is truncated to 192 bits
+    let ecdsa = Ecdsa::new(&named_curve::by_name("P-192").unwrap());
+    let d = hex("6FAB034934E4C0FC9AE67F5B5659A9D7D1FEFD187EE09FD4");
+    assert_eq!(ecdsa.nonce(&d, &sha256(b"sample")), hex("32B1B6D7D42A05CB449065727A84804FB1A3E34D8F261496"));
+    let expected = Signature::new(&hex("4B0B8CE98A92866A2820E20AA6B75B56382E0F9BFD5ECB55"),
+                                  &hex("CCDB006926EA9565CBADC840829D8C384E06DE1F1E381B85"));
+    assert_eq!(ecdsa.sign(&d, b"sample"), expected.normalize_s(&ecdsa.params.n));
+    assert!(ecdsa.verify(&ecdsa.public_key(&d), b"sample", &expected));
+}
+
+#[test]
+fn ecdsa_secp256k1_test() {
+    use rand::{SeedableRng, rngs::StdRng};
+    let ecdsa = Ecdsa::new(&DomainParameters::secp256k1());
+    let n = ecdsa.params.n.clone();
+    let vectors = [
+        (BigInt::one(), "Satoshi Nakamoto", 1,
+         "8F8A276C19F4149656B280621E358CCE24F5F52542772691EE69063B74F15D15",
+         "934B1EA10A4B3C1757E2B0C017D0B6143CE3C9A7E6A4A49860D7A6AB210EE3D8",
+         "2442CE9D2B916064108014783E923EC36B49743E2FFA1C4496F01A512AAFD9E5"),
+        (BigInt::one(), "All those moments will be lost in time, like tears in rain. Time to die...", 0,
+         "38AA22D72376B4DBC472E06C3BA403EE0A394DA63FC58D88686C611ABA98D6B3",
+         "8600DBD41E348FE5C9465AB92D23E3DB8B98B873BEECD930736488696438CB6B",
+         "547FE64427496DB33BF66019DACBF0039C04199ABB0122918601DB38A72CFC21"),
+        (&n - 1, "Satoshi Nakamoto", 0,
+         "33A19B60E25FB6F4435AF53A3D42D493644827367E6453928554F43E49AA6F90",
+         "FD567D121DB66E382991534ADA77A6BD3106F0A1098C231E47993447CD6AF2D0",
+         "6B39CD0EB1BC8603E159EF5C20A5C8AD685A45B06CE9BEBED3F153D10D93BED5"),
+    ];
+    for (d, message, recovery_id, k, r, s) in vectors.iter() {
+        let q = ecdsa.public_key(d);
+        let hash = sha256(message.as_bytes());
+        assert_eq!(ecdsa.nonce(d, &hash), hex(k));
+        let (signature, id) = ecdsa.sign_hash_recoverable(d, &hash);
+        assert_eq!(signature, Signature::new(&hex(r), &hex(s)));
+        assert_eq!(id, *recovery_id);
+        assert_eq!(ecdsa.recover_public_key(&hash, &signature, id), Some(q.clone()));
+        assert_ne!(ecdsa.recover_public_key(&hash, &signature, id ^ 1), Some(q.clone()));
+        assert!(signature.is_low_s(&n));
+        assert!(ecdsa.verify(&q, message.as_bytes(), &signature));
+        let high = Signature::new(&signature.r, &(&n - &signature.s));
+        assert!(!high.is_low_s(&n));
+        assert!(ecdsa.verify(&q, message.as_bytes(), &high));
+    }
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let (d, q) = ecdsa.generate_key(&mut rng);
+    let signature = ecdsa.sign(&d, b"message");
+    assert!(ecdsa.verify(&q, b"message", &signature));
+    assert!(!ecdsa.verify(&ecdsa.public_key(&BigInt::from(2)), b"message", &signature));
+    assert!(!ecdsa.verify(&q, b"message", &Signature::new(&Zero::zero(), &signature.s)));
+    assert!(!ecdsa.verify(&q, b"message", &Signature::new(&signature.r, &n)));
+}
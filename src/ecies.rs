@@ -0,0 +1,175 @@
+use num_bigint::BigInt;
+use rand::Rng;
+use std::fmt;
+use super::aes::Aes;
+use super::domain_parameters::DomainParameters;
+use super::ecdh::{Ecdh, EcdhError, EcdhMode};
+use super::elliptic_curve::{EllipticCurve, ECPoint};
+use super::hash::{hmac_sha256, x963_kdf_sha256};
+use super::sec1::PointEncodingError;
+
+/// symmetric encryption scheme of ECIES, SEC 1 3.8
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EciesCipher {
+    /// message XOR key stream, the key is as long as the message
+    Xor,
+    /// AES-128 in CTR mode with a zero initial counter block
+    Aes128Ctr,
+}
+
+/// reason a ciphertext is rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EciesError {
+    /// shorter than R || tag
+    TooShort,
+    InvalidPointEncoding(PointEncodingError),
+    /// R is not a valid public key or the shared point is infinity
+    InvalidEphemeralKey(EcdhError),
+    InvalidMac,
+}
+
+impl fmt::Display for EciesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EciesError::TooShort => write!(f, "ciphertext too short"),
+            EciesError::InvalidPointEncoding(e) => write!(f, "invalid ephemeral key encoding: {}", e),
+            EciesError::InvalidEphemeralKey(e) => write!(f, "invalid ephemeral key: {}", e),
+            EciesError::InvalidMac => write!(f, "MAC check failed"),
+        }
+    }
+}
+
+/// length of the HMAC-SHA-256 tag and MAC key
+const MAC_LENGTH: usize = 32;
+
+/// ECIES, SEC 1 5.1, with the X9.63 KDF and HMAC-SHA-256
+/// ciphertext R || C || tag, R SEC 1 encoded
+#[derive(Debug, Clone)]
+pub struct Ecies {
+    pub params: DomainParameters,
+    pub cipher: EciesCipher,
+    /// send R compressed
+    pub compressed: bool,
+    ecdh: Ecdh,
+    ec: EllipticCurve,
+}
+
+impl Ecies {
+    pub fn new(params: &DomainParameters, cipher: EciesCipher) -> Ecies {
+        Ecies {
+            params: params.clone(),
+            cipher,
+            compressed: false,
+            ecdh: Ecdh::new(params, EcdhMode::Standard),
+            ec: params.curve(),
+        }
+    }
+
+    /// (EK, MK) from the shared secret
+    fn keys(&self, z: &[u8], message_length: usize, shared_info1: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let enc_length = match self.cipher {
+            EciesCipher::Xor => message_length,
+            EciesCipher::Aes128Ctr => 16,
+        };
+        let mut k = x963_kdf_sha256(z, shared_info1, enc_length + MAC_LENGTH);
+        let mac_key = k.split_off(enc_length);
+        (k, mac_key)
+    }
+
+    /// encryption and decryption are the same for both ciphers
+    fn apply_cipher(&self, key: &[u8], data: &[u8]) -> Vec<u8> {
+        match self.cipher {
+            EciesCipher::Xor => data.iter().zip(key.iter()).map(|(a, b)| a ^ b).collect(),
+            EciesCipher::Aes128Ctr => Aes::new(key).ctr(&[0; 16], data),
+        }
+    }
+
+    /// encryption with the ephemeral private key k, SEC 1 5.1.3
+    pub fn encrypt_with_ephemeral(&self, k: &BigInt, q: &ECPoint, message: &[u8], shared_info1: &[u8],
+        shared_info2: &[u8]) -> Result<Vec<u8>, EcdhError> {
+        let z = self.ecdh.shared_secret(k, q)?;
+        let r = self.ecdh.public_key(k);
+        let (enc_key, mac_key) = self.keys(&z, message.len(), shared_info1);
+        let c = self.apply_cipher(&enc_key, message);
+        let tag = hmac_sha256(&mac_key, &[&c[..], shared_info2].concat());
+        Ok([&self.ec.to_sec1_bytes(&r, self.compressed)[..], &c, &tag].concat())
+    }
+
+    /// encryption to the public key q with a fresh ephemeral key
+    pub fn encrypt<R: Rng + ?Sized>(&self, rng: &mut R, q: &ECPoint, message: &[u8], shared_info1: &[u8],
+        shared_info2: &[u8]) -> Result<Vec<u8>, EcdhError> {
+        let (k, _) = self.ecdh.generate_key(rng);
+        self.encrypt_with_ephemeral(&k, q, message, shared_info1, shared_info2)
+    }
+
+    /// decryption with the private key d, SEC 1 5.1.4
+    pub fn decrypt(&self, d: &BigInt, ciphertext: &[u8], shared_info1: &[u8], shared_info2: &[u8])
+        -> Result<Vec<u8>, EciesError> {
+        // R in either form, its length follows from the first octet
+        let point_length = match ciphertext.first() {
+            Some(2) | Some(3) => 1 + self.ec.field_length(),
+            _ => 1 + 2 * self.ec.field_length(),
+        };
+        if ciphertext.len() < point_length + MAC_LENGTH {
+            return Err(EciesError::TooShort);
+        }
+        let (r, rest) = ciphertext.split_at(point_length);
+        let (c, tag) = rest.split_at(rest.len() - MAC_LENGTH);
+        let r = self.ec.from_sec1_bytes(r).map_err(EciesError::InvalidPointEncoding)?;
+        let z = self.ecdh.shared_secret(d, &r).map_err(EciesError::InvalidEphemeralKey)?;
+        let (enc_key, mac_key) = self.keys(&z, c.len(), shared_info1);
+        let expected = hmac_sha256(&mac_key, &[c, shared_info2].concat());
+        // compare without an early exit
+        if expected.iter().zip(tag.iter()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) != 0 {
+            return Err(EciesError::InvalidMac);
+        }
+        Ok(self.apply_cipher(&enc_key, c))
+    }
+}
+
+#[test]
+fn ecies_test() {
+    use rand::{SeedableRng, rngs::StdRng};
+    use super::hash::{from_hex, to_hex};
+    let hex = |s: &str| BigInt::parse_bytes(s.as_bytes(), 16).unwrap();
+    let params = DomainParameters::secp256k1();
+    let d = hex("1f2e3d4c5b6a79880f1e2d3c4b5a69788796a5b4c3d2e1f00112233445566778");
+    let k = hex("3c4b5a69788796a5b4c3d2e1f001122334455667788990aabbccddeeff001122");
+    let q = Ecdh::new(&params, EcdhMode::Standard).public_key(&d);
+    // ciphertexts computed with an independent implementation
+    let vectors = [
+        (EciesCipher::Aes128Ctr, &b"The quick brown fox jumps over the lazy dog"[..], &b""[..], &b""[..],
+         "04cce4e545ea10b7e58d36104f4d1f666b54d6a14b0bf65105356a8371cc7185492afb585d48dc8334ec3f536536b3f6acfb6497\
+          516d93e8c37f7fde5b11d2107516b135f48bc134b9df05c38b631a5a0c430cbf9d6246adf1a99379daa6b3aef866e9694bbe8656\
+          0afeb88561b82012346cf49fea1d57f61a5ea49952b709428131b31d678ebc3af957313e"),
+        (EciesCipher::Xor, &b"hello ECIES"[..], &b"info1"[..], &b"info2"[..],
+         "04cce4e545ea10b7e58d36104f4d1f666b54d6a14b0bf65105356a8371cc7185492afb585d48dc8334ec3f536536b3f6acfb6497\
+          516d93e8c37f7fde5b11d21075995a1d69fffdd523c0725768ab3bbc942935d067a9799c067f3f32b00a17bec3d6d90294461b1d\
+          ab3a78e4"),
+    ];
+    for (cipher, message, s1, s2, expected) in vectors.iter() {
+        let ecies = Ecies::new(&params, *cipher);
+        let ciphertext = ecies.encrypt_with_ephemeral(&k, &q, message, s1, s2).unwrap();
+        assert_eq!(to_hex(&ciphertext), *expected);
+        assert_eq!(ecies.decrypt(&d, &from_hex(expected).unwrap(), s1, s2).unwrap(), message.to_vec());
+    }
+
+    let ecies = Ecies::new(&params, EciesCipher::Aes128Ctr);
+    let mut rng = StdRng::seed_from_u64(47);
+    let ciphertext = ecies.encrypt(&mut rng, &q, b"round trip", b"", b"label").unwrap();
+    assert_eq!(ecies.decrypt(&d, &ciphertext, b"", b"label").unwrap(), b"round trip".to_vec());
+    assert_eq!(ecies.decrypt(&d, &ciphertext, b"", b"other"), Err(EciesError::InvalidMac));
+    let mut tampered = ciphertext.clone();
+    tampered[70] ^= 1;
+    assert_eq!(ecies.decrypt(&d, &tampered, b"", b"label"), Err(EciesError::InvalidMac));
+    tampered[1] ^= 1;
+    assert_eq!(ecies.decrypt(&d, &tampered, b"", b"label"),
+        Err(EciesError::InvalidPointEncoding(PointEncodingError::NotOnCurve)));
+    assert_eq!(ecies.decrypt(&d, &ciphertext[..90], b"", b""), Err(EciesError::TooShort));
+
+    let mut ecies = Ecies::new(&params, EciesCipher::Xor);
+    ecies.compressed = true;
+    let ciphertext = ecies.encrypt(&mut rng, &q, b"compressed R", b"", b"").unwrap();
+    assert_eq!(ciphertext.len(), 33 + 12 + 32);
+    assert_eq!(ecies.decrypt(&d, &ciphertext, b"", b"").unwrap(), b"compressed R".to_vec());
+}
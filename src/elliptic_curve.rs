@@ -7,7 +7,9 @@ use std::ops::Deref;
 use super::polynomial;
 use super::term_builder::TermBuildable;
 use super::term_builder;
-use crate::bigint::Inverse;
+use super::curve::Curve;
+use super::modular::{BarrettContext, MontgomeryContext, Reduce};
+use crate::bigint::{batch_inverse, inverse_mod, montgomery_context, power_modulo_cached, Inverse, RootModulo};
 use num_traits::Zero;
 use num_traits::One;
 use num_traits::ToPrimitive;
@@ -20,12 +22,16 @@ pub struct EllipticCurve {
     pub b: BigInt,
     pub p: BigInt,
     pol: polynomial::Polynomial,
+    /// reduction modulo p
+    reducer: BarrettContext,
+    /// exponentiation modulo p, for p of 64 bits or more
+    montgomery: Option<MontgomeryContext>,
     /// rational points
     pub points: Vec<ECPoint> // usize
 }
 
 /// Jacobian coordinates point
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ECPoint {
     pub x: BigInt,
     pub y: BigInt,
@@ -44,6 +50,8 @@ impl EllipticCurve {
             b: b.clone(),
             p: p.clone(),
             pol: pol.clone(), 
+            reducer: BarrettContext::new(p),
+            montgomery: montgomery_context(p),
             points: Vec::new(),
         };
         ec.create_points();
@@ -59,12 +67,26 @@ impl EllipticCurve {
             b: b.clone(),
             p: p.clone(),
             pol: pol.clone(), 
+            reducer: BarrettContext::new(p),
+            montgomery: montgomery_context(p),
             points: Vec::new(),
         };
         ec
     }
 
+    /// -16 (4 a^3 + 27 b^2)
+    pub fn discriminant(&self) -> BigInt {
+        let d = BigInt::from(-16) * (BigInt::from(4) * self.a.power(3) + BigInt::from(27) * self.b.power(2));
+        d.mod_floor(&self.p)
+    }
+
+    /// x^3 + a x + b has a multiple root
+    pub fn is_singular(&self) -> bool {
+        self.discriminant().is_zero()
+    }
+
     pub fn j_invariant(&self) -> BigInt {
+        assert!(!self.is_singular(), "j-invariant of singular curve");
         let n = BigInt::from(4) * self.a.power(3);
         let d = n.clone() + BigInt::from(27) * self.b.power(2); 
         let j = BigInt::from(1728) * n.clone() * d.clone().inverse(&self.p);
@@ -84,6 +106,14 @@ impl EllipticCurve {
         r == Zero::zero()
     }
 
+    /// (x, y) with y of the given parity, None if x^3 + a x + b is not a square
+    pub fn lift_x(&self, x: &BigInt, odd_y: bool) -> Option<ECPoint> {
+        let x = x.mod_floor(&self.p);
+        let y = (&x * &x * &x + &self.a * &x + &self.b).sqrt_modulo(&self.p)?;
+        let y = if y.is_odd() == odd_y { y } else { (&self.p - y).mod_floor(&self.p) };
+        Some(ECPoint::new(&x, &y, &One::one()))
+    }
+
     pub fn canonicalize(&self, point: &ECPoint) -> ECPoint {
         ECPoint {
             x: point.x.mod_floor(&self.p),
@@ -92,6 +122,23 @@ impl EllipticCurve {
         }
     }
 
+    /// Jacobian (X, Y, Z) -> (X / Z^2, Y / Z^3, 1) for all points with a single inversion
+    pub fn batch_normalize(&self, points: &mut [ECPoint]) {
+        let p = &self.p;
+        let zs: Vec<BigInt> = points.iter().map(|point| point.z.clone()).collect();
+        let zis = batch_inverse(&zs, p);
+        for (point, zi) in points.iter_mut().zip(zis.iter()) {
+            if point.z.mod_floor(p).is_zero() {
+                *point = ECPoint::infinity();
+                continue;
+            }
+            let zi2 = zi.power(2);
+            point.x = (&point.x * &zi2).mod_floor(p);
+            point.y = (&point.y * &zi2 * zi).mod_floor(p);
+            point.z = One::one();
+        }
+    }
+
     /// Elliptic curve point addition
     pub fn plus(&self, point1: &ECPoint, point2: &ECPoint) -> ECPoint {
         if !self.is_on_curve(point1) {
@@ -103,7 +150,7 @@ impl EllipticCurve {
         if point1.is_infinity() {
             return ECPoint::new(&point2.x, &point2.y, &point2.z);
         } else if point2.is_infinity() {
-            return ECPoint::new(&point1.x, &point2.y, &point2.z);
+            return ECPoint::new(&point1.x, &point1.y, &point1.z);
         }
         let p1 = self.canonicalize(point1);
         let p2 = self.canonicalize(point2);
@@ -112,23 +159,25 @@ impl EllipticCurve {
         let y1 = p1.y.clone();
         let y2 = p2.y.clone();
         if p1.x != p2.x {
-            let m = (&y2 - &y1) * (&x2 - &x1).inverse(&self.p);
+            let m = self.reducer.mul(&(&y2 - &y1), &inverse_mod(&(&x2 - &x1), &self.p).expect("x2 - x1 is not invertible"));
             let x3 = m.power(2) - &x1 - &x2;
             let y3 = m * (&x1 - &x3) - &y1; 
             return ECPoint::new(
-                &x3.mod_floor(&self.p),
-                &y3.mod_floor(&self.p),
+                &self.reducer.reduce(&x3),
+                &self.reducer.reduce(&y3),
                 &BigInt::from(1));
         } else {
             if y1 != y2 || y1 == Zero::zero() {
                 ECPoint::infinity()
             } else {
-                let m = (BigInt::from(3) * x1.power(2) + self.a.clone()) * (BigInt::from(2) * y1.clone()).inverse(&self.p);
+                let m = self.reducer.mul(
+                    &self.reducer.reduce(&(BigInt::from(3) * x1.power(2) + self.a.clone())),
+                    &inverse_mod(&(BigInt::from(2) * &y1), &self.p).expect("2 y1 is not invertible"));
                 let x3 = m.power(2) - BigInt::from(2) * &x1;
                 let y3 = m * (&x1 - &x3) - &y1;
                 return ECPoint::new(
-                    &x3.mod_floor(&self.p),
-                    &y3.mod_floor(&self.p),
+                    &self.reducer.reduce(&x3),
+                    &self.reducer.reduce(&y3),
                     &BigInt::from(1));
             }
         }
@@ -185,7 +234,7 @@ impl EllipticCurve {
             return ECPoint::infinity();
         } else if n < &Zero::zero() {
             let minus_np = self.multiply_scalar(point, &(-n));
-            return self.plus(&ECPoint::infinity(), &minus_np);
+            return self.negate(&minus_np);
         } else if n == &One::one() {
             return point.clone();
         }
@@ -251,6 +300,142 @@ impl EllipticCurve {
         let b = (&self.b - BigInt::from(7) * w).div_floor(&self.p);
         EllipticCurve::new(&a, &b, &self.p)
     }
+
+    /// curve whose j-invariant is j
+    /// j = 0: y^2 = x^3 + 1
+    /// j = 1728: y^2 = x^3 + x
+    /// otherwise: y^2 = x^3 + 3k x + 2k, k = j / (1728 - j)
+    /// built with new_raw, call create_points to enumerate the points of a small curve
+    pub fn from_j_invariant(j: &BigInt, p: &BigInt) -> EllipticCurve {
+        assert!(p > &BigInt::from(3));
+        let j = j.mod_floor(p);
+        let j1728 = BigInt::from(1728).mod_floor(p);
+        if j.is_zero() {
+            EllipticCurve::new_raw(&Zero::zero(), &One::one(), p)
+        } else if j == j1728 {
+            EllipticCurve::new_raw(&One::one(), &Zero::zero(), p)
+        } else {
+            let k = (&j * (&j1728 - &j).inverse(p)).mod_floor(p);
+            let a = (BigInt::from(3) * &k).mod_floor(p);
+            let b = (BigInt::from(2) * &k).mod_floor(p);
+            EllipticCurve::new_raw(&a, &b, p)
+        }
+    }
+
+    /// isomorphism to other
+    /// return: u, (x, y) -> (u^2 x, u^3 y) maps self to other
+    pub fn is_isomorphic(&self, other: &EllipticCurve) -> Option<BigInt> {
+        if self.p != other.p {
+            return None;
+        }
+        let p = &self.p;
+        let a1 = self.a.mod_floor(p);
+        let b1 = self.b.mod_floor(p);
+        let a2 = other.a.mod_floor(p);
+        let b2 = other.b.mod_floor(p);
+        if a1.is_zero() != a2.is_zero() || b1.is_zero() != b2.is_zero() {
+            return None;
+        }
+        // a2 = u^4 a1, b2 = u^6 b1
+        let is_isomorphism = |u: &BigInt| {
+            (u.power(4) * &a1 - &a2).mod_floor(p).is_zero()
+                && (u.power(6) * &b1 - &b2).mod_floor(p).is_zero()
+        };
+        let mut candidates: Vec<BigInt> = Vec::new();
+        if a1.is_zero() && b1.is_zero() {
+            candidates.push(One::one());
+        } else if a1.is_zero() {
+            // u^3 = +-sqrt(b2 / b1)
+            if let Some(s) = (&b2 * b1.inverse(p)).sqrt_modulo(p) {
+                for t in [s.clone(), -s] {
+                    candidates.extend(t.root_modulo(3, p));
+                }
+            }
+        } else if b1.is_zero() {
+            // u^2 = +-sqrt(a2 / a1)
+            if let Some(s) = (&a2 * a1.inverse(p)).sqrt_modulo(p) {
+                for t in [s.clone(), -s] {
+                    candidates.extend(t.sqrt_modulo(p));
+                }
+            }
+        } else {
+            // u^2 = (a1 b2) / (a2 b1)
+            let t = &a1 * &b2 * (&a2 * &b1).inverse(p);
+            candidates.extend(t.sqrt_modulo(p));
+        }
+        candidates.into_iter().find(|u| is_isomorphism(u))
+    }
+
+    /// x^n (mod p) with the cached context
+    pub fn power_modulo(&self, x: &BigInt, n: &BigInt) -> BigInt {
+        power_modulo_cached(x, n, &self.p, self.montgomery.as_ref())
+    }
+
+    /// automorphisms u, (x, y) -> (u^2 x, u^3 y)
+    /// {+-1} in general, the 4th roots of unity for j = 1728
+    /// and the 6th roots of unity for j = 0
+    pub fn automorphism_group(&self) -> Vec<BigInt> {
+        let p = &self.p;
+        let n = if self.a.mod_floor(p).is_zero() {
+            6
+        } else if self.b.mod_floor(p).is_zero() {
+            4
+        } else {
+            2
+        };
+        let mut group: Vec<BigInt> = Vec::new();
+        let m = Integer::gcd(&BigInt::from(n), &(p - 1));
+        let e = (p - 1) / &m;
+        let mut h = BigInt::from(1);
+        loop {
+            // generator of the m-th roots of unity
+            let g = self.power_modulo(&h, &e);
+            let is_generator = (1..m.to_i32().unwrap())
+                .all(|i| self.power_modulo(&g, &BigInt::from(i)) != One::one());
+            if is_generator {
+                let mut u: BigInt = One::one();
+                for _ in 0..m.to_i32().unwrap() {
+                    group.push(u.clone());
+                    u = (u * &g).mod_floor(p);
+                }
+                break;
+            }
+            h += 1;
+        }
+        group.sort();
+        group
+    }
+}
+
+impl Curve for EllipticCurve {
+    type Point = ECPoint;
+
+    fn identity(&self) -> ECPoint {
+        ECPoint::infinity()
+    }
+
+    fn is_on_curve(&self, point: &ECPoint) -> bool {
+        EllipticCurve::is_on_curve(self, point)
+    }
+
+    fn negate(&self, point: &ECPoint) -> ECPoint {
+        EllipticCurve::negate(self, point)
+    }
+
+    fn plus(&self, point1: &ECPoint, point2: &ECPoint) -> ECPoint {
+        EllipticCurve::plus(self, point1, point2)
+    }
+
+    fn normalize(&self, point: &ECPoint) -> ECPoint {
+        if point.is_infinity() {
+            return ECPoint::infinity();
+        }
+        self.canonicalize(point)
+    }
+
+    fn multiply_scalar(&self, point: &ECPoint, n: &BigInt) -> ECPoint {
+        EllipticCurve::multiply_scalar(self, point, n)
+    }
 }
 
 impl ECPoint {
@@ -275,6 +460,10 @@ impl ECPoint {
 
 impl fmt::Display for EllipticCurve {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_singular() {
+            return write!(f, "F_{}: y^2 = {}, cardinality:{}, singular",
+                self.p, self.pol, self.cardinality());
+        }
         write!(f, "F_{}: y^2 = {}, cardinality:{}, j:{}",
             self.p, self.pol, self.cardinality(), self.j_invariant()) 
     }
@@ -411,6 +600,30 @@ fn elliptic_curve_test5() {
     }
 }
 
+#[test]
+fn plus_identity_test() {
+    let ec = EllipticCurve::new(&BigInt::from(2), &BigInt::from(3), &BigInt::from(97));
+    let p = ECPoint::new(&BigInt::from(3), &BigInt::from(6), &BigInt::from(1));
+    // P + O used to return (P.x, O.y, O.z), i.e. infinity
+    assert_eq!(ec.plus(&p, &ECPoint::infinity()), p);
+    assert_eq!(ec.plus(&ECPoint::infinity(), &p), p);
+    assert!(ec.plus(&ECPoint::infinity(), &ECPoint::infinity()).is_infinity());
+}
+
+#[test]
+fn multiply_scalar_negative_test() {
+    let ec = EllipticCurve::new(&BigInt::from(2), &BigInt::from(3), &BigInt::from(97));
+    let p = ECPoint::new(&BigInt::from(3), &BigInt::from(6), &BigInt::from(1));
+    // (-1) P used to return P
+    assert_eq!(ec.multiply_scalar(&p, &BigInt::from(-1)), ECPoint::new(&BigInt::from(3), &BigInt::from(91), &BigInt::from(1)));
+    for k in 1..6 {
+        let kp = ec.multiply_scalar(&p, &BigInt::from(k));
+        let minus_kp = ec.multiply_scalar(&p, &BigInt::from(-k));
+        assert_eq!(minus_kp, ec.negate(&kp));
+        assert!(ec.plus(&kp, &minus_kp).is_infinity());
+    }
+}
+
 #[test]
 fn isogeny_test1() {
     let ec = EllipticCurve::new(&BigInt::from(1), &BigInt::from(1), &BigInt::from(19));
@@ -488,3 +701,98 @@ fn isogeny_test3() {
     */
 }
 
+
+#[test]
+fn discriminant_test() {
+    let ec = EllipticCurve::new(&BigInt::from(1), &BigInt::from(1), &BigInt::from(5));
+    assert_eq_str!(ec.discriminant(), "4");
+    assert!(!ec.is_singular());
+    let ec = EllipticCurve::new(&BigInt::from(0), &BigInt::from(0), &BigInt::from(19));
+    assert!(ec.is_singular());
+    assert_eq_str!(ec, "F_19: y^2 = x^3, cardinality:20, singular");
+    // (x - 1)^2 (x + 2)
+    let ec = EllipticCurve::new(&BigInt::from(-3), &BigInt::from(2), &BigInt::from(11));
+    assert!(ec.is_singular());
+}
+
+#[test]
+fn from_j_invariant_test() {
+    let p = BigInt::from(23);
+    for j in [0, 20, 22, 1728] {
+        let ec = EllipticCurve::from_j_invariant(&BigInt::from(j), &p);
+        assert_eq!(ec.j_invariant(), BigInt::from(j).mod_floor(&p));
+    }
+    let mut ec = EllipticCurve::from_j_invariant(&BigInt::from(20), &p);
+    ec.create_points();
+    assert_eq_str!(ec, "F_23: y^2 = x^3 + 10 x + 22, cardinality:18, j:20");
+    // p above 2^64, no point enumeration
+    let p = BigInt::parse_bytes(b"fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f", 16).unwrap();
+    let j = BigInt::from(123_456_789);
+    assert_eq!(EllipticCurve::from_j_invariant(&j, &p).j_invariant(), j);
+}
+
+#[test]
+fn is_isomorphic_test() {
+    let p = BigInt::from(5);
+    let ec = EllipticCurve::new(&BigInt::from(1), &BigInt::from(1), &p);
+    // u = 2: a' = 2^4, b' = 2^6
+    let ec2 = EllipticCurve::new(&BigInt::from(1), &BigInt::from(4), &p);
+    let u = ec.is_isomorphic(&ec2).unwrap();
+    assert_eq!(ec.cardinality(), ec2.cardinality());
+    for point in ec.points() {
+        if !point.is_infinity() {
+            let image = ECPoint::new(
+                &(u.power(2) * &point.x).mod_floor(&p),
+                &(u.power(3) * &point.y).mod_floor(&p),
+                &One::one());
+            assert!(ec2.is_on_curve(&image));
+        }
+    }
+    // quadratic twist has the same j-invariant
+    let twist = EllipticCurve::new(&BigInt::from(4), &BigInt::from(3), &p);
+    assert_eq!(ec.j_invariant(), twist.j_invariant());
+    assert_eq!(ec.is_isomorphic(&twist), None);
+}
+
+#[test]
+fn is_isomorphic_test2() {
+    let p = BigInt::from(13);
+    // j = 0
+    let ec = EllipticCurve::new(&BigInt::from(0), &BigInt::from(1), &p);
+    let u = ec.is_isomorphic(&EllipticCurve::new(&BigInt::from(0), &BigInt::from(64), &p)).unwrap();
+    assert_eq!(u.power(6).mod_floor(&p), BigInt::from(64).mod_floor(&p));
+    // j = 1728
+    let ec = EllipticCurve::new(&BigInt::from(1), &BigInt::from(0), &p);
+    let u = ec.is_isomorphic(&EllipticCurve::new(&BigInt::from(81), &BigInt::from(0), &p)).unwrap();
+    assert_eq!(u.power(4).mod_floor(&p), BigInt::from(81).mod_floor(&p));
+    assert_eq!(ec.is_isomorphic(&EllipticCurve::new(&BigInt::from(2), &BigInt::from(0), &p)), None);
+}
+
+#[test]
+fn automorphism_group_test() {
+    let group = |a: i32, b: i32, p: i32| -> String {
+        let ec = EllipticCurve::new(&BigInt::from(a), &BigInt::from(b), &BigInt::from(p));
+        let v: Vec<String> = ec.automorphism_group().iter().map(|u| u.to_string()).collect();
+        v.join(", ")
+    };
+    assert_eq!(group(1, 1, 5), "1, 4");
+    // j = 0
+    assert_eq!(group(0, 1, 7), "1, 2, 3, 4, 5, 6");
+    assert_eq!(group(0, 1, 5), "1, 4");
+    // j = 1728
+    assert_eq!(group(1, 0, 13), "1, 5, 8, 12");
+    assert_eq!(group(1, 0, 7), "1, 6");
+}
+
+#[test]
+fn batch_normalize_test() {
+    use crate::point_representation::{JacobianPoint, PointRepresentation, AffinePoint};
+    let ec = EllipticCurve::new(&BigInt::from(2), &BigInt::from(3), &BigInt::from(97));
+    let g = JacobianPoint::from(AffinePoint::new(&BigInt::from(3), &BigInt::from(6)));
+    let mut points: Vec<ECPoint> = (0..6).map(|k| ECPoint::from(g.multiply(&BigInt::from(k), &ec))).collect();
+    ec.batch_normalize(&mut points);
+    for (k, point) in points.iter().enumerate() {
+        let expected = ec.multiply_scalar(&ECPoint::new(&BigInt::from(3), &BigInt::from(6), &BigInt::from(1)), &BigInt::from(k));
+        assert_eq!(point, &expected);
+    }
+}
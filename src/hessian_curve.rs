@@ -0,0 +1,218 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Zero, One};
+use crate::bigint::{Inverse, Power};
+use super::curve::Curve;
+use super::elliptic_curve::{EllipticCurve, ECPoint};
+
+/// x^3 + y^3 + 1 = 3 d x y
+/// X^3 + Y^3 + Z^3 = 3 d X Y Z
+/// GF(p), p > 3
+#[derive(Debug, Clone)]
+pub struct HessianCurve {
+    pub d: BigInt,
+    pub p: BigInt,
+}
+
+/// (X : Y : Z) on a Hessian curve
+/// Z = 0 holds the identity and two more points, test with Curve::is_identity
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HessianPoint {
+    pub x: BigInt,
+    pub y: BigInt,
+    pub z: BigInt,
+}
+
+impl HessianPoint {
+    pub fn new(x: &BigInt, y: &BigInt, z: &BigInt) -> HessianPoint {
+        HessianPoint {
+            x: x.clone(),
+            y: y.clone(),
+            z: z.clone(),
+        }
+    }
+}
+
+impl HessianCurve {
+    pub fn new(d: &BigInt, p: &BigInt) -> HessianCurve {
+        assert!(p > &BigInt::from(3));
+        let curve = HessianCurve {
+            d: d.mod_floor(p),
+            p: p.clone(),
+        };
+        assert!(!(curve.d.power(3) - BigInt::from(1)).mod_floor(p).is_zero(), "singular curve");
+        curve
+    }
+
+    /// (1 : -1 : 0)
+    pub fn identity(&self) -> HessianPoint {
+        HessianPoint::new(&One::one(), &(&self.p - 1), &Zero::zero())
+    }
+
+    /// 27 d^3 (d^3 + 8)^3 / (d^3 - 1)^3
+    pub fn j_invariant(&self) -> BigInt {
+        let p = &self.p;
+        let d3 = self.d.power(3);
+        let n = BigInt::from(27) * &d3 * (&d3 + BigInt::from(8)).power(3);
+        let d = (&d3 - BigInt::from(1)).power(3);
+        (n * d.inverse(p)).mod_floor(p)
+    }
+
+    pub fn is_on_curve(&self, point: &HessianPoint) -> bool {
+        let lhs = point.x.power(3) + point.y.power(3) + point.z.power(3);
+        let rhs = BigInt::from(3) * &self.d * &point.x * &point.y * &point.z;
+        !(point.x.is_zero() && point.y.is_zero() && point.z.is_zero())
+            && (lhs - rhs).mod_floor(&self.p).is_zero()
+    }
+
+    /// Z = 1, or X = 1 for the points at infinity
+    pub fn normalize(&self, point: &HessianPoint) -> HessianPoint {
+        let p = &self.p;
+        let i = if point.z.mod_floor(p).is_zero() {
+            point.x.inverse(p)
+        } else {
+            point.z.inverse(p)
+        };
+        HessianPoint::new(
+            &(&point.x * &i).mod_floor(p),
+            &(&point.y * &i).mod_floor(p),
+            &(&point.z * &i).mod_floor(p))
+    }
+
+    /// Point negation: -(X : Y : Z) = (Y : X : Z)
+    pub fn negate(&self, point: &HessianPoint) -> HessianPoint {
+        HessianPoint::new(&point.y, &point.x, &point.z)
+    }
+
+    /// Point addition
+    /// X3 = Y1^2 X2 Z2 - Y2^2 X1 Z1
+    /// Y3 = X1^2 Y2 Z2 - X2^2 Y1 Z1
+    /// Z3 = Z1^2 X2 Y2 - Z2^2 X1 Y1
+    /// falls back to the rotated formula when the result is (0 : 0 : 0), e.g. for doubling
+    pub fn plus(&self, point1: &HessianPoint, point2: &HessianPoint) -> HessianPoint {
+        let p = &self.p;
+        let (x1, y1, z1) = (&point1.x, &point1.y, &point1.z);
+        let (x2, y2, z2) = (&point2.x, &point2.y, &point2.z);
+        let x3 = (y1.power(2) * x2 * z2 - y2.power(2) * x1 * z1).mod_floor(p);
+        let y3 = (x1.power(2) * y2 * z2 - x2.power(2) * y1 * z1).mod_floor(p);
+        let z3 = (z1.power(2) * x2 * y2 - z2.power(2) * x1 * y1).mod_floor(p);
+        if !(x3.is_zero() && y3.is_zero() && z3.is_zero()) {
+            return self.normalize(&HessianPoint::new(&x3, &y3, &z3));
+        }
+        let x3 = x1.power(2) * x2 * y2 - y1 * z1 * z2.power(2);
+        let y3 = z1.power(2) * x2 * z2 - x1 * y1 * y2.power(2);
+        let z3 = y1.power(2) * y2 * z2 - x1 * z1 * x2.power(2);
+        self.normalize(&HessianPoint::new(&x3, &y3, &z3))
+    }
+
+    /// rational points
+    pub fn points(&self) -> Vec<HessianPoint> {
+        let mut points: Vec<HessianPoint> = Vec::new();
+        for x in num_iter::range(BigInt::from(0), self.p.clone()) {
+            for y in num_iter::range(BigInt::from(0), self.p.clone()) {
+                let point = HessianPoint::new(&x, &y, &One::one());
+                if self.is_on_curve(&point) {
+                    points.push(point);
+                }
+            }
+        }
+        // (1 : y : 0) with y^3 = -1
+        for y in num_iter::range(BigInt::from(0), self.p.clone()) {
+            let point = HessianPoint::new(&One::one(), &y, &Zero::zero());
+            if self.is_on_curve(&point) {
+                points.push(point);
+            }
+        }
+        points
+    }
+
+    /// v^2 = u^3 - 27 d (d^3 + 8) u + 54 (d^6 - 20 d^3 - 8)
+    pub fn to_weierstrass(&self) -> EllipticCurve {
+        let p = &self.p;
+        let d3 = self.d.power(3);
+        let a = BigInt::from(-27) * &self.d * (&d3 + BigInt::from(8));
+        let b = BigInt::from(54) * (d3.power(2) - BigInt::from(20) * &d3 - BigInt::from(8));
+        EllipticCurve::new_raw(&a.mod_floor(p), &b.mod_floor(p), p)
+    }
+
+    /// u = 12 (d^3 - 1) Z / (d Z + X + Y) - 9 d^2
+    /// v = 36 (d^3 - 1) (X - Y) / (d Z + X + Y)
+    pub fn to_weierstrass_point(&self, point: &HessianPoint) -> ECPoint {
+        let p = &self.p;
+        let s = (&self.d * &point.z + &point.x + &point.y).mod_floor(p);
+        if s.is_zero() {
+            return ECPoint::infinity();
+        }
+        let si = s.inverse(p);
+        let e = self.d.power(3) - BigInt::from(1);
+        let u = BigInt::from(12) * &e * &point.z * &si - BigInt::from(9) * self.d.power(2);
+        let v = BigInt::from(36) * &e * (&point.x - &point.y) * &si;
+        ECPoint::new(&u.mod_floor(p), &v.mod_floor(p), &One::one())
+    }
+
+    /// w = u + 9 d^2
+    /// (X : Y : Z) = (3 (12 (d^3 - 1) - d w) + v : 3 (12 (d^3 - 1) - d w) - v : 6 w)
+    pub fn from_weierstrass_point(&self, point: &ECPoint) -> HessianPoint {
+        if point.is_infinity() {
+            return self.identity();
+        }
+        let w = &point.x + BigInt::from(9) * self.d.power(2);
+        let t = BigInt::from(3) * (BigInt::from(12) * (self.d.power(3) - BigInt::from(1)) - &self.d * &w);
+        let x = &t + &point.y;
+        let y = &t - &point.y;
+        let z = BigInt::from(6) * &w;
+        self.normalize(&HessianPoint::new(&x, &y, &z))
+    }
+}
+
+impl Curve for HessianCurve {
+    type Point = HessianPoint;
+
+    fn identity(&self) -> HessianPoint {
+        HessianCurve::identity(self)
+    }
+
+    fn is_on_curve(&self, point: &HessianPoint) -> bool {
+        HessianCurve::is_on_curve(self, point)
+    }
+
+    fn negate(&self, point: &HessianPoint) -> HessianPoint {
+        HessianCurve::negate(self, point)
+    }
+
+    fn plus(&self, point1: &HessianPoint, point2: &HessianPoint) -> HessianPoint {
+        HessianCurve::plus(self, point1, point2)
+    }
+
+    fn normalize(&self, point: &HessianPoint) -> HessianPoint {
+        HessianCurve::normalize(self, point)
+    }
+}
+
+#[test]
+fn hessian_curve_test() {
+    // d = 5 is singular since 5^3 = 1 mod 31
+    let p = BigInt::from(31);
+    let curve = HessianCurve::new(&BigInt::from(2), &p);
+    let points = curve.points();
+    let ec = curve.to_weierstrass();
+    assert_eq!(points.len(), EllipticCurve::new(&ec.a, &ec.b, &p).cardinality());
+    assert_eq!(curve.j_invariant(), ec.j_invariant());
+    // (1 : y : 0) with y^3 = -1, only (1 : -1 : 0) is the identity
+    let at_infinity: Vec<&HessianPoint> = points.iter().filter(|point| point.z.is_zero()).collect();
+    assert_eq!(at_infinity.len(), 3);
+    assert_eq!(at_infinity.iter().filter(|point| curve.is_identity(point)).count(), 1);
+    let n = BigInt::from(points.len());
+    for point1 in &points {
+        assert_eq!(curve.multiply_scalar(point1, &n), curve.identity());
+        let image1 = curve.to_weierstrass_point(point1);
+        assert!(ec.is_on_curve(&image1));
+        assert_eq!(&curve.from_weierstrass_point(&image1), point1);
+        for point2 in &points {
+            let sum = curve.plus(point1, point2);
+            assert!(curve.is_on_curve(&sum));
+            let image2 = curve.to_weierstrass_point(point2);
+            assert_eq!(curve.to_weierstrass_point(&sum), ec.plus(&image1, &image2));
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Zero, One};
+use crate::bigint::{Inverse, Power};
+use super::curve::Curve;
+use super::elliptic_curve::{EllipticCurve, ECPoint};
+use super::weierstrass_curve::WeierstrassCurve;
+
+/// a x (y^2 - 1) = b y (x^2 - 1)
+/// a X (Y^2 - Z^2) = b Y (X^2 - Z^2)
+/// GF(p), p > 3
+#[derive(Debug, Clone)]
+pub struct HuffCurve {
+    pub a: BigInt,
+    pub b: BigInt,
+    pub p: BigInt,
+}
+
+/// (X : Y : Z) on a Huff curve
+/// Z = 0 holds the three points of order 2, test for the identity with Curve::is_identity
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HuffPoint {
+    pub x: BigInt,
+    pub y: BigInt,
+    pub z: BigInt,
+}
+
+impl HuffPoint {
+    pub fn new(x: &BigInt, y: &BigInt, z: &BigInt) -> HuffPoint {
+        HuffPoint {
+            x: x.clone(),
+            y: y.clone(),
+            z: z.clone(),
+        }
+    }
+}
+
+impl HuffCurve {
+    pub fn new(a: &BigInt, b: &BigInt, p: &BigInt) -> HuffCurve {
+        assert!(p > &BigInt::from(3));
+        let curve = HuffCurve {
+            a: a.mod_floor(p),
+            b: b.mod_floor(p),
+            p: p.clone(),
+        };
+        let d = &curve.a * &curve.b * (curve.a.power(2) - curve.b.power(2));
+        assert!(!d.mod_floor(p).is_zero(), "singular curve");
+        curve
+    }
+
+    /// (0 : 0 : 1)
+    pub fn identity(&self) -> HuffPoint {
+        HuffPoint::new(&Zero::zero(), &Zero::zero(), &One::one())
+    }
+
+    /// y^2 = x (x + a^2) (x + b^2)
+    pub fn weierstrass(&self) -> WeierstrassCurve {
+        let a2 = self.a.power(2);
+        let b2 = self.b.power(2);
+        WeierstrassCurve::new(&Zero::zero(), &(&a2 + &b2), &Zero::zero(), &(&a2 * &b2), &Zero::zero(), &self.p)
+    }
+
+    pub fn j_invariant(&self) -> BigInt {
+        self.weierstrass().j_invariant()
+    }
+
+    pub fn is_on_curve(&self, point: &HuffPoint) -> bool {
+        let (x, y, z) = (&point.x, &point.y, &point.z);
+        let lhs = &self.a * x * (y.power(2) - z.power(2));
+        let rhs = &self.b * y * (x.power(2) - z.power(2));
+        !(x.is_zero() && y.is_zero() && z.is_zero())
+            && (lhs - rhs).mod_floor(&self.p).is_zero()
+    }
+
+    /// Z = 1, or the first nonzero coordinate = 1 for the points at infinity
+    pub fn normalize(&self, point: &HuffPoint) -> HuffPoint {
+        let p = &self.p;
+        let i = if !point.z.mod_floor(p).is_zero() {
+            point.z.inverse(p)
+        } else if !point.x.mod_floor(p).is_zero() {
+            point.x.inverse(p)
+        } else {
+            point.y.inverse(p)
+        };
+        HuffPoint::new(
+            &(&point.x * &i).mod_floor(p),
+            &(&point.y * &i).mod_floor(p),
+            &(&point.z * &i).mod_floor(p))
+    }
+
+    /// Point negation: -(X : Y : Z) = (-X : -Y : Z)
+    pub fn negate(&self, point: &HuffPoint) -> HuffPoint {
+        let p = &self.p;
+        HuffPoint::new(&(-&point.x).mod_floor(p), &(-&point.y).mod_floor(p), &point.z)
+    }
+
+    /// Point addition (Joye, Tibouchi, Vergnaud)
+    /// X3 = (X1 Z2 + X2 Z1) (Z1 Z2 + Y1 Y2)^2 (Z1 Z2 - X1 X2)
+    /// Y3 = (Y1 Z2 + Y2 Z1) (Z1 Z2 + X1 X2)^2 (Z1 Z2 - Y1 Y2)
+    /// Z3 = (Z1^2 Z2^2 - X1^2 X2^2) (Z1^2 Z2^2 - Y1^2 Y2^2)
+    /// exceptional cases go through the Weierstrass form
+    pub fn plus(&self, point1: &HuffPoint, point2: &HuffPoint) -> HuffPoint {
+        let p = &self.p;
+        let (x1, y1, z1) = (&point1.x, &point1.y, &point1.z);
+        let (x2, y2, z2) = (&point2.x, &point2.y, &point2.z);
+        let zz = z1 * z2;
+        let xx = x1 * x2;
+        let yy = y1 * y2;
+        let x3 = (x1 * z2 + x2 * z1) * (&zz + &yy).power(2) * (&zz - &xx);
+        let y3 = (y1 * z2 + y2 * z1) * (&zz + &xx).power(2) * (&zz - &yy);
+        let z3 = (zz.power(2) - xx.power(2)) * (zz.power(2) - yy.power(2));
+        let x3 = x3.mod_floor(p);
+        let y3 = y3.mod_floor(p);
+        let z3 = z3.mod_floor(p);
+        if !(x3.is_zero() && y3.is_zero() && z3.is_zero()) {
+            return self.normalize(&HuffPoint::new(&x3, &y3, &z3));
+        }
+        let w = self.weierstrass();
+        let sum = w.plus(&self.to_long_weierstrass_point(point1), &self.to_long_weierstrass_point(point2));
+        self.from_long_weierstrass_point(&sum)
+    }
+
+    /// rational points
+    pub fn points(&self) -> Vec<HuffPoint> {
+        let mut points: Vec<HuffPoint> = Vec::new();
+        for x in num_iter::range(BigInt::from(0), self.p.clone()) {
+            for y in num_iter::range(BigInt::from(0), self.p.clone()) {
+                let point = HuffPoint::new(&x, &y, &One::one());
+                if self.is_on_curve(&point) {
+                    points.push(point);
+                }
+            }
+        }
+        // (1 : 0 : 0), (0 : 1 : 0), (a : b : 0)
+        points.push(HuffPoint::new(&One::one(), &Zero::zero(), &Zero::zero()));
+        points.push(HuffPoint::new(&Zero::zero(), &One::one(), &Zero::zero()));
+        points.push(self.normalize(&HuffPoint::new(&self.a, &self.b, &Zero::zero())));
+        points
+    }
+
+    /// x = a b (a y - b x) / (a x - b y)
+    /// y = a b (a^2 - b^2) x y (b x - a y) / (a x - b y)^2
+    pub fn to_long_weierstrass_point(&self, point: &HuffPoint) -> ECPoint {
+        let p = &self.p;
+        let (a, b) = (&self.a, &self.b);
+        let point = self.normalize(point);
+        let d = (a * &point.x - b * &point.y).mod_floor(p);
+        if d.is_zero() {
+            return ECPoint::infinity();
+        }
+        let di = d.inverse(p);
+        let u = (a * b * (a * &point.y - b * &point.x) * &di).mod_floor(p);
+        if point.z.is_zero() {
+            // points at infinity are the points of order 2
+            return ECPoint::new(&u, &Zero::zero(), &One::one());
+        }
+        let v = a * b * (a.power(2) - b.power(2)) * &point.x * &point.y * (b * &point.x - a * &point.y) * di.power(2);
+        ECPoint::new(&u, &v.mod_floor(p), &One::one())
+    }
+
+    /// t = y / x = a (u + b^2) / b (u + a^2)
+    /// x = v (a - b t)^2 / a b (a^2 - b^2) t (b - a t)
+    pub fn from_long_weierstrass_point(&self, point: &ECPoint) -> HuffPoint {
+        let p = &self.p;
+        let (a, b) = (&self.a, &self.b);
+        if point.is_infinity() {
+            return self.identity();
+        }
+        let u = point.x.mod_floor(p);
+        if (&u + b.power(2)).mod_floor(p).is_zero() {
+            return HuffPoint::new(&One::one(), &Zero::zero(), &Zero::zero());
+        }
+        if (&u + a.power(2)).mod_floor(p).is_zero() {
+            return HuffPoint::new(&Zero::zero(), &One::one(), &Zero::zero());
+        }
+        if u.is_zero() {
+            return self.normalize(&HuffPoint::new(a, b, &Zero::zero()));
+        }
+        let t = (a * (&u + b.power(2)) * (b * (&u + a.power(2))).inverse(p)).mod_floor(p);
+        let d = a * b * (a.power(2) - b.power(2)) * &t * (b - a * &t);
+        let x = (&point.y * (a - b * &t).power(2) * d.inverse(p)).mod_floor(p);
+        HuffPoint::new(&x, &(&t * &x).mod_floor(p), &One::one())
+    }
+
+    pub fn to_weierstrass(&self) -> EllipticCurve {
+        self.weierstrass().to_short_weierstrass()
+    }
+
+    pub fn to_weierstrass_point(&self, point: &HuffPoint) -> ECPoint {
+        self.weierstrass().map_to_short(&self.to_long_weierstrass_point(point))
+    }
+
+    pub fn from_weierstrass_point(&self, point: &ECPoint) -> HuffPoint {
+        self.from_long_weierstrass_point(&self.weierstrass().map_from_short(point))
+    }
+}
+
+impl Curve for HuffCurve {
+    type Point = HuffPoint;
+
+    fn identity(&self) -> HuffPoint {
+        HuffCurve::identity(self)
+    }
+
+    fn is_on_curve(&self, point: &HuffPoint) -> bool {
+        HuffCurve::is_on_curve(self, point)
+    }
+
+    fn negate(&self, point: &HuffPoint) -> HuffPoint {
+        HuffCurve::negate(self, point)
+    }
+
+    fn plus(&self, point1: &HuffPoint, point2: &HuffPoint) -> HuffPoint {
+        HuffCurve::plus(self, point1, point2)
+    }
+
+    fn normalize(&self, point: &HuffPoint) -> HuffPoint {
+        HuffCurve::normalize(self, point)
+    }
+}
+
+#[test]
+fn huff_curve_test() {
+    let p = BigInt::from(31);
+    let curve = HuffCurve::new(&BigInt::from(2), &BigInt::from(5), &p);
+    let points = curve.points();
+    assert_eq!(points.len(), 32);
+    let ec = curve.to_weierstrass();
+    assert_eq!(points.len(), EllipticCurve::new(&ec.a, &ec.b, &p).cardinality());
+    assert_eq!(curve.j_invariant(), ec.j_invariant());
+    let n = BigInt::from(points.len());
+    for point1 in &points {
+        assert_eq!(curve.multiply_scalar(point1, &n), curve.identity());
+        let image1 = curve.to_weierstrass_point(point1);
+        assert!(ec.is_on_curve(&image1));
+        assert_eq!(&curve.from_weierstrass_point(&image1), point1);
+        for point2 in &points {
+            let sum = curve.plus(point1, point2);
+            assert!(curve.is_on_curve(&sum));
+            let image2 = curve.to_weierstrass_point(point2);
+            assert_eq!(curve.to_weierstrass_point(&sum), ec.plus(&image1, &image2));
+        }
+    }
+}
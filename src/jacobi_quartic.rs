@@ -0,0 +1,238 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Zero, One};
+use crate::bigint::{Inverse, Power};
+use super::curve::Curve;
+use super::elliptic_curve::{EllipticCurve, ECPoint};
+use super::weierstrass_curve::WeierstrassCurve;
+
+/// y^2 = x^4 + 2 a x^2 + 1
+/// Y^2 = X^4 + 2 a X^2 Z^2 + Z^4, x = X / Z, y = Y / Z^2
+/// GF(p), p > 3
+#[derive(Debug, Clone)]
+pub struct JacobiQuartic {
+    pub a: BigInt,
+    pub p: BigInt,
+}
+
+/// (X : Y : Z) on a Jacobi quartic
+/// Z = 0 holds (1 : 1 : 0) and (1 : -1 : 0), test for the identity with Curve::is_identity
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JacobiQuarticPoint {
+    pub x: BigInt,
+    pub y: BigInt,
+    pub z: BigInt,
+}
+
+impl JacobiQuarticPoint {
+    pub fn new(x: &BigInt, y: &BigInt, z: &BigInt) -> JacobiQuarticPoint {
+        JacobiQuarticPoint {
+            x: x.clone(),
+            y: y.clone(),
+            z: z.clone(),
+        }
+    }
+}
+
+impl JacobiQuartic {
+    pub fn new(a: &BigInt, p: &BigInt) -> JacobiQuartic {
+        assert!(p > &BigInt::from(3));
+        let curve = JacobiQuartic {
+            a: a.mod_floor(p),
+            p: p.clone(),
+        };
+        assert!(!(curve.a.power(2) - BigInt::from(1)).mod_floor(p).is_zero(), "singular curve");
+        curve
+    }
+
+    /// (0, 1)
+    pub fn identity(&self) -> JacobiQuarticPoint {
+        JacobiQuarticPoint::new(&Zero::zero(), &One::one(), &One::one())
+    }
+
+    /// y^2 = x^3 + 2 a x^2 - 4 x - 8 a
+    pub fn weierstrass(&self) -> WeierstrassCurve {
+        let a2 = BigInt::from(2) * &self.a;
+        let a6 = BigInt::from(-8) * &self.a;
+        WeierstrassCurve::new(&Zero::zero(), &a2, &Zero::zero(), &BigInt::from(-4), &a6, &self.p)
+    }
+
+    /// 64 (a^2 + 3)^3 / (a^2 - 1)^2
+    pub fn j_invariant(&self) -> BigInt {
+        let p = &self.p;
+        let a2 = self.a.power(2);
+        let n = BigInt::from(64) * (&a2 + BigInt::from(3)).power(3);
+        let d = (&a2 - BigInt::from(1)).power(2);
+        (n * d.inverse(p)).mod_floor(p)
+    }
+
+    pub fn is_on_curve(&self, point: &JacobiQuarticPoint) -> bool {
+        let (x, y, z) = (&point.x, &point.y, &point.z);
+        let lhs = y.power(2);
+        let rhs = x.power(4) + BigInt::from(2) * &self.a * x.power(2) * z.power(2) + z.power(4);
+        !(x.is_zero() && y.is_zero() && z.is_zero())
+            && (lhs - rhs).mod_floor(&self.p).is_zero()
+    }
+
+    /// (x, y, 1), or (1 : y : 0) for the points at infinity
+    pub fn normalize(&self, point: &JacobiQuarticPoint) -> JacobiQuarticPoint {
+        let p = &self.p;
+        if point.z.mod_floor(p).is_zero() {
+            let y = (&point.y * point.x.power(2).inverse(p)).mod_floor(p);
+            return JacobiQuarticPoint::new(&One::one(), &y, &Zero::zero());
+        }
+        let zi = point.z.inverse(p);
+        let x = (&point.x * &zi).mod_floor(p);
+        let y = (&point.y * zi.power(2)).mod_floor(p);
+        JacobiQuarticPoint::new(&x, &y, &One::one())
+    }
+
+    /// Point negation: -(X : Y : Z) = (-X : Y : Z)
+    pub fn negate(&self, point: &JacobiQuarticPoint) -> JacobiQuarticPoint {
+        JacobiQuarticPoint::new(&(-&point.x).mod_floor(&self.p), &point.y, &point.z)
+    }
+
+    /// Point addition
+    /// x3 = (x1 y2 + y1 x2) / (1 - x1^2 x2^2)
+    /// y3 = ((1 + x1^2 x2^2) (y1 y2 + 2 a x1 x2) + 2 x1 x2 (x1^2 + x2^2)) / (1 - x1^2 x2^2)^2
+    /// exceptional cases go through the Weierstrass form
+    pub fn plus(&self, point1: &JacobiQuarticPoint, point2: &JacobiQuarticPoint) -> JacobiQuarticPoint {
+        let p = &self.p;
+        let point1 = self.normalize(point1);
+        let point2 = self.normalize(point2);
+        let (x1, y1) = (&point1.x, &point1.y);
+        let (x2, y2) = (&point2.x, &point2.y);
+        let xx = x1 * x2;
+        let d = (BigInt::from(1) - xx.power(2)).mod_floor(p);
+        if point1.z.is_zero() || point2.z.is_zero() || d.is_zero() {
+            let w = self.weierstrass();
+            let sum = w.plus(&self.to_long_weierstrass_point(&point1), &self.to_long_weierstrass_point(&point2));
+            return self.from_long_weierstrass_point(&sum);
+        }
+        let di = d.inverse(p);
+        let x3 = (x1 * y2 + y1 * x2) * &di;
+        let y3 = ((BigInt::from(1) + xx.power(2)) * (y1 * y2 + BigInt::from(2) * &self.a * &xx)
+            + BigInt::from(2) * &xx * (x1.power(2) + x2.power(2))) * di.power(2);
+        JacobiQuarticPoint::new(&x3.mod_floor(p), &y3.mod_floor(p), &One::one())
+    }
+
+    /// rational points
+    pub fn points(&self) -> Vec<JacobiQuarticPoint> {
+        let mut points: Vec<JacobiQuarticPoint> = Vec::new();
+        for x in num_iter::range(BigInt::from(0), self.p.clone()) {
+            for y in num_iter::range(BigInt::from(0), self.p.clone()) {
+                let point = JacobiQuarticPoint::new(&x, &y, &One::one());
+                if self.is_on_curve(&point) {
+                    points.push(point);
+                }
+            }
+        }
+        // (1 : 1 : 0), (1 : -1 : 0)
+        points.push(JacobiQuarticPoint::new(&One::one(), &One::one(), &Zero::zero()));
+        points.push(JacobiQuarticPoint::new(&One::one(), &(&self.p - 1), &Zero::zero()));
+        points
+    }
+
+    /// (x, y) -> (2 (y + 1) / x^2, 4 (y + 1 + a x^2) / x^3)
+    pub fn to_long_weierstrass_point(&self, point: &JacobiQuarticPoint) -> ECPoint {
+        let p = &self.p;
+        let point = self.normalize(point);
+        let (x, y) = (&point.x, &point.y);
+        if point.z.is_zero() {
+            // (1 : +-1 : 0) -> (+-2, 0)
+            return ECPoint::new(&(BigInt::from(2) * y).mod_floor(p), &Zero::zero(), &One::one());
+        }
+        if x.is_zero() {
+            if y.is_one() {
+                return ECPoint::infinity();
+            }
+            // (0, -1) -> (-2 a, 0)
+            return ECPoint::new(&(BigInt::from(-2) * &self.a).mod_floor(p), &Zero::zero(), &One::one());
+        }
+        let xi = x.inverse(p);
+        let u = BigInt::from(2) * (y + BigInt::from(1)) * xi.power(2);
+        let v = BigInt::from(4) * (y + BigInt::from(1) + &self.a * x.power(2)) * xi.power(3);
+        ECPoint::new(&u.mod_floor(p), &v.mod_floor(p), &One::one())
+    }
+
+    /// (u, v) -> (2 (u + 2 a) / v, u x^2 / 2 - 1)
+    pub fn from_long_weierstrass_point(&self, point: &ECPoint) -> JacobiQuarticPoint {
+        let p = &self.p;
+        if point.is_infinity() {
+            return self.identity();
+        }
+        let u = point.x.mod_floor(p);
+        if point.y.mod_floor(p).is_zero() {
+            if (&u - BigInt::from(2)).mod_floor(p).is_zero() {
+                return JacobiQuarticPoint::new(&One::one(), &One::one(), &Zero::zero());
+            }
+            if (&u + BigInt::from(2)).mod_floor(p).is_zero() {
+                return JacobiQuarticPoint::new(&One::one(), &(p - 1), &Zero::zero());
+            }
+            return JacobiQuarticPoint::new(&Zero::zero(), &(p - 1), &One::one());
+        }
+        let x = BigInt::from(2) * (&u + BigInt::from(2) * &self.a) * point.y.inverse(p);
+        let y = &u * x.power(2) * BigInt::from(2).inverse(p) - BigInt::from(1);
+        JacobiQuarticPoint::new(&x.mod_floor(p), &y.mod_floor(p), &One::one())
+    }
+
+    pub fn to_weierstrass(&self) -> EllipticCurve {
+        self.weierstrass().to_short_weierstrass()
+    }
+
+    pub fn to_weierstrass_point(&self, point: &JacobiQuarticPoint) -> ECPoint {
+        self.weierstrass().map_to_short(&self.to_long_weierstrass_point(point))
+    }
+
+    pub fn from_weierstrass_point(&self, point: &ECPoint) -> JacobiQuarticPoint {
+        self.from_long_weierstrass_point(&self.weierstrass().map_from_short(point))
+    }
+}
+
+impl Curve for JacobiQuartic {
+    type Point = JacobiQuarticPoint;
+
+    fn identity(&self) -> JacobiQuarticPoint {
+        JacobiQuartic::identity(self)
+    }
+
+    fn is_on_curve(&self, point: &JacobiQuarticPoint) -> bool {
+        JacobiQuartic::is_on_curve(self, point)
+    }
+
+    fn negate(&self, point: &JacobiQuarticPoint) -> JacobiQuarticPoint {
+        JacobiQuartic::negate(self, point)
+    }
+
+    fn plus(&self, point1: &JacobiQuarticPoint, point2: &JacobiQuarticPoint) -> JacobiQuarticPoint {
+        JacobiQuartic::plus(self, point1, point2)
+    }
+
+    fn normalize(&self, point: &JacobiQuarticPoint) -> JacobiQuarticPoint {
+        JacobiQuartic::normalize(self, point)
+    }
+}
+
+#[test]
+fn jacobi_quartic_test() {
+    let p = BigInt::from(31);
+    let curve = JacobiQuartic::new(&BigInt::from(3), &p);
+    let points = curve.points();
+    assert_eq!(points.len(), 32);
+    let ec = curve.to_weierstrass();
+    assert_eq!(points.len(), EllipticCurve::new(&ec.a, &ec.b, &p).cardinality());
+    assert_eq!(curve.j_invariant(), ec.j_invariant());
+    let n = BigInt::from(points.len());
+    for point1 in &points {
+        assert_eq!(curve.multiply_scalar(point1, &n), curve.identity());
+        let image1 = curve.to_weierstrass_point(point1);
+        assert!(ec.is_on_curve(&image1));
+        assert_eq!(&curve.from_weierstrass_point(&image1), point1);
+        for point2 in &points {
+            let sum = curve.plus(point1, point2);
+            assert!(curve.is_on_curve(&sum));
+            let image2 = curve.to_weierstrass_point(point2);
+            assert_eq!(curve.to_weierstrass_point(&sum), ec.plus(&image1, &image2));
+        }
+    }
+}
@@ -0,0 +1,417 @@
+use num_bigint::{BigInt, Sign};
+use num_traits::Zero;
+use std::fmt;
+use super::asn1::{self, Asn1Error, DerReader};
+use super::base58::{self, Base58Error};
+use super::domain_parameters::DomainParameters;
+use super::elliptic_curve::ECPoint;
+use super::named_curve;
+use super::scalar::Scalar;
+use super::sec1::PointEncodingError;
+
+/// id-ecPublicKey, RFC 5480
+const EC_PUBLIC_KEY_OID: &str = "1.2.840.10045.2.1";
+
+/// reason a key file is rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyFormatError {
+    Asn1(Asn1Error),
+    Base58(Base58Error),
+    /// missing BEGIN / END lines or invalid base64
+    InvalidPem,
+    /// algorithm other than id-ecPublicKey or an unsupported version
+    UnsupportedAlgorithm,
+    /// curve parameters missing or not a named curve of this crate
+    UnsupportedCurve,
+    /// private key out of [1, n) or of the wrong length
+    InvalidPrivateKey,
+    InvalidPublicKey(PointEncodingError),
+    /// the embedded public key is not d G
+    PublicKeyMismatch,
+    /// WIF with an unknown version byte or suffix
+    InvalidWif,
+}
+
+impl fmt::Display for KeyFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeyFormatError::Asn1(e) => write!(f, "{}", e),
+            KeyFormatError::Base58(e) => write!(f, "{}", e),
+            KeyFormatError::InvalidPem => write!(f, "invalid PEM"),
+            KeyFormatError::UnsupportedAlgorithm => write!(f, "unsupported key algorithm"),
+            KeyFormatError::UnsupportedCurve => write!(f, "unsupported curve"),
+            KeyFormatError::InvalidPrivateKey => write!(f, "invalid private key"),
+            KeyFormatError::InvalidPublicKey(e) => write!(f, "invalid public key: {}", e),
+            KeyFormatError::PublicKeyMismatch => write!(f, "public key does not match private key"),
+            KeyFormatError::InvalidWif => write!(f, "invalid WIF"),
+        }
+    }
+}
+
+impl From<Asn1Error> for KeyFormatError {
+    fn from(e: Asn1Error) -> KeyFormatError {
+        KeyFormatError::Asn1(e)
+    }
+}
+
+impl From<Base58Error> for KeyFormatError {
+    fn from(e: Base58Error) -> KeyFormatError {
+        KeyFormatError::Base58(e)
+    }
+}
+
+/// Bitcoin network of WIF and extended keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+}
+
+fn curve_oid(params: &DomainParameters) -> Vec<u8> {
+    assert!(!params.oid.is_empty(), "curve {} has no OID", params.name);
+    asn1::encode_oid(params.oid)
+}
+
+fn curve_by_oid(oid: &str) -> Result<DomainParameters, KeyFormatError> {
+    named_curve::by_oid(oid).ok_or(KeyFormatError::UnsupportedCurve)
+}
+
+/// d as ceil(bits(n) / 8) big-endian bytes, SEC 1 2.3.7
+fn private_key_bytes(params: &DomainParameters, d: &BigInt) -> Vec<u8> {
+    assert!(d > &Zero::zero() && d < &params.n, "private key out of range");
+    let (_, bytes) = d.to_bytes_be();
+    let mut out = vec![0u8; params.n.bits().div_ceil(8) - bytes.len()];
+    out.extend_from_slice(&bytes);
+    out
+}
+
+/// ECPrivateKey, SEC 1 C.4, with the curve OID unless it comes from an enclosing PKCS #8 structure
+fn encode_ec_private_key_with(params: &DomainParameters, d: &BigInt, with_parameters: bool) -> Vec<u8> {
+    let ec = params.curve();
+    let q = ec.multiply_scalar(&params.g, d);
+    let mut values = vec![
+        asn1::encode_unsigned(&BigInt::from(1)),
+        asn1::encode(asn1::OCTET_STRING, &private_key_bytes(params, d)),
+    ];
+    if with_parameters {
+        values.push(asn1::encode(asn1::context(0), &curve_oid(params)));
+    }
+    values.push(asn1::encode(asn1::context(1), &asn1::encode_bit_string(&ec.to_sec1_bytes(&q, false))));
+    asn1::encode_sequence(&values)
+}
+
+/// ECPrivateKey of the given curve, or of the curve named inside when params is None
+fn decode_ec_private_key_with(der: &[u8], params: Option<DomainParameters>)
+    -> Result<(DomainParameters, BigInt), KeyFormatError> {
+    let mut outer = DerReader::new(der);
+    let mut reader = outer.read_sequence()?;
+    outer.finish()?;
+    if reader.read_unsigned()? != BigInt::from(1) {
+        return Err(KeyFormatError::UnsupportedAlgorithm);
+    }
+    let key = reader.read(asn1::OCTET_STRING)?;
+    let named = match reader.read_optional(asn1::context(0))? {
+        Some(content) => {
+            let mut inner = DerReader::new(content);
+            let oid = inner.read_oid()?;
+            inner.finish()?;
+            Some(curve_by_oid(&oid)?)
+        }
+        None => None,
+    };
+    let params = match (params, named) {
+        (Some(params), Some(named)) if params != named => return Err(KeyFormatError::UnsupportedCurve),
+        (Some(params), _) | (None, Some(params)) => params,
+        (None, None) => return Err(KeyFormatError::UnsupportedCurve),
+    };
+    let d = BigInt::from_bytes_be(Sign::Plus, key);
+    if key.len() != params.n.bits().div_ceil(8) || d.is_zero() || d >= params.n {
+        return Err(KeyFormatError::InvalidPrivateKey);
+    }
+    if let Some(content) = reader.read_optional(asn1::context(1))? {
+        let mut inner = DerReader::new(content);
+        let ec = params.curve();
+        let q = ec.from_sec1_bytes(inner.read_bit_string()?).map_err(KeyFormatError::InvalidPublicKey)?;
+        inner.finish()?;
+        if q != ec.multiply_scalar(&params.g, &d) {
+            return Err(KeyFormatError::PublicKeyMismatch);
+        }
+    }
+    reader.finish()?;
+    Ok((params, d))
+}
+
+/// SEC 1 ECPrivateKey DER with the named curve and the uncompressed public key, "EC PRIVATE KEY" in PEM
+pub fn encode_ec_private_key(params: &DomainParameters, d: &BigInt) -> Vec<u8> {
+    encode_ec_private_key_with(params, d, true)
+}
+
+pub fn decode_ec_private_key(der: &[u8]) -> Result<(DomainParameters, BigInt), KeyFormatError> {
+    decode_ec_private_key_with(der, None)
+}
+
+/// AlgorithmIdentifier { id-ecPublicKey, namedCurve }
+fn algorithm_identifier(params: &DomainParameters) -> Vec<u8> {
+    asn1::encode_sequence(&[asn1::encode_oid(EC_PUBLIC_KEY_OID), curve_oid(params)])
+}
+
+fn read_algorithm_identifier(reader: &mut DerReader) -> Result<DomainParameters, KeyFormatError> {
+    let mut algorithm = reader.read_sequence()?;
+    if algorithm.read_oid()? != EC_PUBLIC_KEY_OID {
+        return Err(KeyFormatError::UnsupportedAlgorithm);
+    }
+    if algorithm.peek_tag() != Some(asn1::OBJECT_IDENTIFIER) {
+        // explicit or implicit parameters
+        return Err(KeyFormatError::UnsupportedCurve);
+    }
+    let params = curve_by_oid(&algorithm.read_oid()?)?;
+    algorithm.finish()?;
+    Ok(params)
+}
+
+/// PKCS #8 PrivateKeyInfo DER, RFC 5208, "PRIVATE KEY" in PEM
+pub fn encode_pkcs8_private_key(params: &DomainParameters, d: &BigInt) -> Vec<u8> {
+    asn1::encode_sequence(&[
+        asn1::encode_unsigned(&Zero::zero()),
+        algorithm_identifier(params),
+        asn1::encode(asn1::OCTET_STRING, &encode_ec_private_key_with(params, d, false)),
+    ])
+}
+
+pub fn decode_pkcs8_private_key(der: &[u8]) -> Result<(DomainParameters, BigInt), KeyFormatError> {
+    let mut outer = DerReader::new(der);
+    let mut reader = outer.read_sequence()?;
+    outer.finish()?;
+    if !reader.read_unsigned()?.is_zero() {
+        return Err(KeyFormatError::UnsupportedAlgorithm);
+    }
+    let params = read_algorithm_identifier(&mut reader)?;
+    let key = reader.read(asn1::OCTET_STRING)?;
+    // optional attributes [0] are ignored
+    reader.read_optional(asn1::context(0))?;
+    reader.finish()?;
+    decode_ec_private_key_with(key, Some(params))
+}
+
+/// SubjectPublicKeyInfo DER, RFC 5480, "PUBLIC KEY" in PEM
+pub fn encode_public_key(params: &DomainParameters, q: &ECPoint, compressed: bool) -> Vec<u8> {
+    assert!(!q.is_infinity(), "public key is infinity");
+    let point = params.curve().to_sec1_bytes(q, compressed);
+    asn1::encode_sequence(&[algorithm_identifier(params), asn1::encode_bit_string(&point)])
+}
+
+pub fn decode_public_key(der: &[u8]) -> Result<(DomainParameters, ECPoint), KeyFormatError> {
+    let mut outer = DerReader::new(der);
+    let mut reader = outer.read_sequence()?;
+    outer.finish()?;
+    let params = read_algorithm_identifier(&mut reader)?;
+    let point = reader.read_bit_string()?;
+    reader.finish()?;
+    let q = params.curve().from_sec1_bytes(point).map_err(KeyFormatError::InvalidPublicKey)?;
+    if q.is_infinity() {
+        return Err(KeyFormatError::InvalidPublicKey(PointEncodingError::InvalidLength));
+    }
+    Ok((params, q))
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as usize) << 16 | (b[1] as usize) << 8 | b[2] as usize;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i)) & 0x3f] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.as_bytes();
+    if !s.len().is_multiple_of(4) {
+        return None;
+    }
+    let mut out: Vec<u8> = Vec::with_capacity(s.len() / 4 * 3);
+    for (i, chunk) in s.chunks(4).enumerate() {
+        let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+        if padding > 2 || (padding > 0 && i + 1 != s.len() / 4) {
+            return None;
+        }
+        let mut n = 0usize;
+        for c in &chunk[..4 - padding] {
+            n = n << 6 | BASE64_ALPHABET.iter().position(|a| a == c)?;
+        }
+        n <<= 6 * padding;
+        out.extend_from_slice(&[(n >> 16) as u8, (n >> 8) as u8, n as u8][..3 - padding]);
+    }
+    Some(out)
+}
+
+/// RFC 7468 textual encoding with 64 character lines
+pub fn pem_encode(label: &str, der: &[u8]) -> String {
+    let body = base64_encode(der);
+    let mut pem = format!("-----BEGIN {}-----\n", label);
+    for line in body.as_bytes().chunks(64) {
+        pem.push_str(std::str::from_utf8(line).unwrap());
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {}-----\n", label));
+    pem
+}
+
+/// (label, DER) of the first PEM block
+pub fn pem_decode(pem: &str) -> Result<(String, Vec<u8>), KeyFormatError> {
+    let mut lines = pem.lines().map(|line| line.trim()).skip_while(|line| !line.starts_with("-----BEGIN "));
+    let label = lines.next()
+        .and_then(|line| line.strip_prefix("-----BEGIN ")?.strip_suffix("-----"))
+        .ok_or(KeyFormatError::InvalidPem)?
+        .to_string();
+    let end = format!("-----END {}-----", label);
+    let mut body = String::new();
+    for line in lines.by_ref() {
+        if line == end {
+            let der = base64_decode(&body).ok_or(KeyFormatError::InvalidPem)?;
+            return Ok((label, der));
+        }
+        body.push_str(line);
+    }
+    Err(KeyFormatError::InvalidPem)
+}
+
+/// PKCS #8 PEM
+pub fn private_key_to_pem(params: &DomainParameters, d: &BigInt) -> String {
+    pem_encode("PRIVATE KEY", &encode_pkcs8_private_key(params, d))
+}
+
+/// "PRIVATE KEY" (PKCS #8) or "EC PRIVATE KEY" (SEC 1) PEM
+pub fn private_key_from_pem(pem: &str) -> Result<(DomainParameters, BigInt), KeyFormatError> {
+    match pem_decode(pem)? {
+        (ref label, ref der) if label == "PRIVATE KEY" => decode_pkcs8_private_key(der),
+        (ref label, ref der) if label == "EC PRIVATE KEY" => decode_ec_private_key(der),
+        _ => Err(KeyFormatError::InvalidPem),
+    }
+}
+
+pub fn public_key_to_pem(params: &DomainParameters, q: &ECPoint, compressed: bool) -> String {
+    pem_encode("PUBLIC KEY", &encode_public_key(params, q, compressed))
+}
+
+pub fn public_key_from_pem(pem: &str) -> Result<(DomainParameters, ECPoint), KeyFormatError> {
+    match pem_decode(pem)? {
+        (ref label, ref der) if label == "PUBLIC KEY" => decode_public_key(der),
+        _ => Err(KeyFormatError::InvalidPem),
+    }
+}
+
+/// Bitcoin wallet import format of a secp256k1 secret key
+/// Base58Check(0x80 or 0xef || d || 0x01 if the public key is compressed)
+pub fn wif_encode(secret_key: &Scalar, compressed: bool, network: Network) -> String {
+    assert!(!secret_key.is_zero(), "secret key is zero");
+    let version = match network {
+        Network::Mainnet => 0x80,
+        Network::Testnet => 0xef,
+    };
+    let mut data = vec![version];
+    data.extend_from_slice(&secret_key.to_be_bytes());
+    if compressed {
+        data.push(1);
+    }
+    base58::encode_check(&data)
+}
+
+/// (secret key, compressed, network)
+pub fn wif_decode(s: &str) -> Result<(Scalar, bool, Network), KeyFormatError> {
+    let data = base58::decode_check(s)?;
+    let network = match data.first() {
+        Some(0x80) => Network::Mainnet,
+        Some(0xef) => Network::Testnet,
+        _ => return Err(KeyFormatError::InvalidWif),
+    };
+    let compressed = match data.len() {
+        33 => false,
+        34 if data[33] == 1 => true,
+        _ => return Err(KeyFormatError::InvalidWif),
+    };
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&data[1..33]);
+    match Scalar::from_be_bytes(&bytes) {
+        Some(d) if !d.is_zero() => Ok((d, compressed, network)),
+        _ => Err(KeyFormatError::InvalidPrivateKey),
+    }
+}
+
+#[test]
+fn key_format_test() {
+    let hex = |s: &str| BigInt::parse_bytes(s.as_bytes(), 16).unwrap();
+    // keys generated by OpenSSL 3.5
+    macro_rules! fixture {
+        ($name:expr, $d:expr) => {
+            ($name, $d, [
+                include_str!(concat!("../testdata/keys/", $name, "_sec1.pem")),
+                include_str!(concat!("../testdata/keys/", $name, "_pkcs8.pem")),
+                include_str!(concat!("../testdata/keys/", $name, "_spki.pem")),
+                include_str!(concat!("../testdata/keys/", $name, "_spki_compressed.pem")),
+            ])
+        };
+    }
+    let fixtures = [
+        fixture!("secp256k1", "77a0093ca33ef97e2f34d640bedc4b298d609fe1dba1d0ca7a6ecffa4d988488"),
+        fixture!("prime256v1", "da498957136668ed3384dbafa5ea7cc8fb601f9673aaf07895f07110549a2a99"),
+        fixture!("secp384r1", "4e4940fec8c41286a7a28cf5598fc3abe367fe59358042bea69c607a76bc22506eb5924ab494d6704da6de04ab4ecc61"),
+    ];
+    for (name, d, [sec1, pkcs8, spki, compressed]) in fixtures.iter() {
+        let params = named_curve::by_name(name).unwrap();
+        let d = hex(d);
+        assert_eq!(private_key_from_pem(sec1), Ok((params.clone(), d.clone())));
+        assert_eq!(private_key_from_pem(pkcs8), Ok((params.clone(), d.clone())));
+        assert_eq!(pem_encode("EC PRIVATE KEY", &encode_ec_private_key(&params, &d)), *sec1);
+        assert_eq!(private_key_to_pem(&params, &d), *pkcs8);
+
+        let (decoded, q) = public_key_from_pem(spki).unwrap();
+        assert_eq!(decoded, params);
+        assert_eq!(q, params.curve().multiply_scalar(&params.g, &d));
+        assert_eq!(public_key_to_pem(&params, &q, false), *spki);
+        assert_eq!(public_key_from_pem(compressed), Ok((params.clone(), q.clone())));
+        assert_eq!(public_key_to_pem(&params, &q, true), *compressed);
+    }
+
+    let params = DomainParameters::secp256k1();
+    let d = hex(fixtures[0].1);
+    let mut der = encode_ec_private_key(&params, &d);
+    let last = der.len() - 1;
+    der[last] ^= 1;
+    assert!(matches!(decode_ec_private_key(&der), Err(KeyFormatError::InvalidPublicKey(_))));
+    der[last] ^= 1;
+    der[7] ^= 1;
+    assert_eq!(decode_ec_private_key(&der), Err(KeyFormatError::PublicKeyMismatch));
+    // SEC 1 version 1 is not a PKCS #8 version
+    assert_eq!(decode_pkcs8_private_key(&encode_ec_private_key(&params, &d)), Err(KeyFormatError::UnsupportedAlgorithm));
+    assert_eq!(private_key_from_pem("MIGE"), Err(KeyFormatError::InvalidPem));
+    assert_eq!(base64_decode("aGVsbG8="), Some(b"hello".to_vec()));
+    assert_eq!(base64_encode(b"hello!"), "aGVsbG8h");
+}
+
+#[test]
+fn wif_test() {
+    // Bitcoin wiki example key
+    let bytes = super::hash::from_hex("0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d").unwrap();
+    let d = Scalar::from_bytes_reduced(&bytes);
+    let vectors = [
+        ("5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ", false, Network::Mainnet),
+        ("KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617", true, Network::Mainnet),
+        ("cMzLdeGd5vEqxB8B6VFQoRopQ3sLAAvEzDAoQgvX54xwofSWj1fx", true, Network::Testnet),
+    ];
+    for (wif, compressed, network) in vectors.iter() {
+        assert_eq!(wif_encode(&d, *compressed, *network), *wif);
+        assert_eq!(wif_decode(wif), Ok((d, *compressed, *network)));
+    }
+    assert_eq!(wif_decode("5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTK"),
+        Err(KeyFormatError::Base58(Base58Error::InvalidChecksum)));
+    assert_eq!(wif_decode(&base58::encode_check(&[0x80, 1, 2])), Err(KeyFormatError::InvalidWif));
+}
@@ -0,0 +1,127 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Zero, One};
+use crate::bigint::{Inverse, Power};
+use super::curve::Curve;
+use super::elliptic_curve::{EllipticCurve, ECPoint};
+use super::weierstrass_curve::WeierstrassCurve;
+
+/// y^2 = x (x - 1) (x - lambda)
+/// GF(p), p > 3
+#[derive(Debug, Clone)]
+pub struct LegendreCurve {
+    pub lambda: BigInt,
+    pub p: BigInt,
+}
+
+impl LegendreCurve {
+    pub fn new(lambda: &BigInt, p: &BigInt) -> LegendreCurve {
+        assert!(p > &BigInt::from(3));
+        let curve = LegendreCurve {
+            lambda: lambda.mod_floor(p),
+            p: p.clone(),
+        };
+        assert!(!(&curve.lambda * (&curve.lambda - BigInt::from(1))).mod_floor(p).is_zero(), "singular curve");
+        curve
+    }
+
+    /// y^2 = x^3 - (1 + lambda) x^2 + lambda x
+    pub fn weierstrass(&self) -> WeierstrassCurve {
+        let a2 = -(BigInt::from(1) + &self.lambda);
+        WeierstrassCurve::new(&Zero::zero(), &a2, &Zero::zero(), &self.lambda, &Zero::zero(), &self.p)
+    }
+
+    /// 256 (lambda^2 - lambda + 1)^3 / lambda^2 (lambda - 1)^2
+    pub fn j_invariant(&self) -> BigInt {
+        let p = &self.p;
+        let l = &self.lambda;
+        let n = BigInt::from(256) * (l.power(2) - l + BigInt::from(1)).power(3);
+        let d = (l * (l - BigInt::from(1))).power(2);
+        (n * d.inverse(p)).mod_floor(p)
+    }
+
+    pub fn is_on_curve(&self, point: &ECPoint) -> bool {
+        self.weierstrass().is_on_curve(point)
+    }
+
+    pub fn negate(&self, point: &ECPoint) -> ECPoint {
+        self.weierstrass().negate(point)
+    }
+
+    pub fn plus(&self, point1: &ECPoint, point2: &ECPoint) -> ECPoint {
+        self.weierstrass().plus(point1, point2)
+    }
+
+    /// rational points
+    pub fn points(&self) -> Vec<ECPoint> {
+        self.weierstrass().points()
+    }
+
+    pub fn to_weierstrass(&self) -> EllipticCurve {
+        self.weierstrass().to_short_weierstrass()
+    }
+
+    pub fn to_weierstrass_point(&self, point: &ECPoint) -> ECPoint {
+        self.weierstrass().map_to_short(point)
+    }
+
+    pub fn from_weierstrass_point(&self, point: &ECPoint) -> ECPoint {
+        self.weierstrass().map_from_short(point)
+    }
+}
+
+impl Curve for LegendreCurve {
+    type Point = ECPoint;
+
+    fn identity(&self) -> ECPoint {
+        ECPoint::infinity()
+    }
+
+    fn is_on_curve(&self, point: &ECPoint) -> bool {
+        LegendreCurve::is_on_curve(self, point)
+    }
+
+    fn negate(&self, point: &ECPoint) -> ECPoint {
+        LegendreCurve::negate(self, point)
+    }
+
+    fn plus(&self, point1: &ECPoint, point2: &ECPoint) -> ECPoint {
+        LegendreCurve::plus(self, point1, point2)
+    }
+
+    fn normalize(&self, point: &ECPoint) -> ECPoint {
+        if point.is_infinity() {
+            return ECPoint::infinity();
+        }
+        ECPoint::new(&point.x.mod_floor(&self.p), &point.y.mod_floor(&self.p), &One::one())
+    }
+}
+
+#[test]
+fn legendre_curve_test() {
+    let p = BigInt::from(31);
+    let curve = LegendreCurve::new(&BigInt::from(5), &p);
+    let points = curve.points();
+    let ec = curve.to_weierstrass();
+    assert_eq!(points.len(), EllipticCurve::new(&ec.a, &ec.b, &p).cardinality());
+    assert_eq!(curve.j_invariant(), ec.j_invariant());
+    // full 2-torsion, so the order is divisible by 4
+    assert!(points.len().is_multiple_of(4));
+    let n = BigInt::from(points.len());
+    for point1 in &points {
+        assert!(curve.multiply_scalar(point1, &n).is_infinity());
+        let image1 = curve.to_weierstrass_point(point1);
+        assert!(ec.is_on_curve(&image1));
+        assert_eq!(&curve.from_weierstrass_point(&image1), point1);
+        for point2 in &points {
+            let image2 = curve.to_weierstrass_point(point2);
+            assert_eq!(curve.to_weierstrass_point(&curve.plus(point1, point2)), ec.plus(&image1, &image2));
+        }
+    }
+
+    // lambda, 1 / lambda, 1 - lambda, ... give the same j
+    let other = LegendreCurve::new(&BigInt::from(5).inverse(&p), &p);
+    assert_eq!(other.j_invariant(), curve.j_invariant());
+    let other = LegendreCurve::new(&BigInt::from(-4), &p);
+    assert_eq!(other.j_invariant(), curve.j_invariant());
+}
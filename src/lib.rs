@@ -17,3 +17,31 @@ pub mod j_invariant;
 pub mod subscripted_variable;
 pub mod simultaneous_equation;
 pub mod secp256k1;
+pub mod singular_cubic;
+pub mod weierstrass_curve;
+pub mod montgomery_curve;
+pub mod twisted_edwards_curve;
+pub mod hessian_curve;
+pub mod huff_curve;
+pub mod jacobi_quartic;
+pub mod legendre_curve;
+pub mod curve;
+pub mod point;
+pub mod point_representation;
+pub mod modular;
+pub mod u256;
+pub mod scalar;
+pub mod domain_parameters;
+pub mod named_curve;
+pub mod hash;
+pub mod ecdsa;
+pub mod schnorr;
+pub mod batch;
+pub mod ecdh;
+pub mod aes;
+pub mod ecies;
+pub mod sec1;
+pub mod asn1;
+pub mod base58;
+pub mod key_format;
+pub mod bip32;
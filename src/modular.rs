@@ -0,0 +1,205 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Zero, One};
+use crate::bigint::inverse_mod;
+
+/// x (mod n) for a fixed modulus n
+pub trait Reduce {
+    fn modulus(&self) -> &BigInt;
+
+    /// 0 <= result < n
+    fn reduce(&self, x: &BigInt) -> BigInt;
+
+    /// a b (mod n)
+    fn mul(&self, a: &BigInt, b: &BigInt) -> BigInt {
+        self.reduce(&(a * b))
+    }
+}
+
+impl<T: Reduce + ?Sized> Reduce for &T {
+    fn modulus(&self) -> &BigInt {
+        (**self).modulus()
+    }
+
+    fn reduce(&self, x: &BigInt) -> BigInt {
+        (**self).reduce(x)
+    }
+}
+
+impl Reduce for BigInt {
+    fn modulus(&self) -> &BigInt {
+        self
+    }
+
+    fn reduce(&self, x: &BigInt) -> BigInt {
+        x.mod_floor(self)
+    }
+}
+
+/// Barrett reduction
+/// mu = floor(4^k / n), k = bits of n
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BarrettContext {
+    pub n: BigInt,
+    k: usize,
+    mu: BigInt,
+}
+
+impl BarrettContext {
+    pub fn new(n: &BigInt) -> BarrettContext {
+        assert!(n >= &BigInt::from(2));
+        let k = n.bits();
+        let mu = (BigInt::one() << (2 * k)) / n;
+        BarrettContext { n: n.clone(), k, mu }
+    }
+
+    /// x (mod n) for 0 <= x < n^2
+    fn reduce_nonnegative(&self, x: &BigInt) -> BigInt {
+        let q = ((x >> (self.k - 1)) * &self.mu) >> (self.k + 1);
+        let mut r = x - q * &self.n;
+        while r >= self.n {
+            r -= &self.n;
+        }
+        r
+    }
+}
+
+impl Reduce for BarrettContext {
+    fn modulus(&self) -> &BigInt {
+        &self.n
+    }
+
+    fn reduce(&self, x: &BigInt) -> BigInt {
+        if x.bits() > 2 * self.k {
+            return x.mod_floor(&self.n);
+        }
+        if x < &Zero::zero() {
+            let r = self.reduce_nonnegative(&(-x));
+            return if r.is_zero() { r } else { &self.n - r };
+        }
+        self.reduce_nonnegative(x)
+    }
+}
+
+/// Montgomery multiplication for odd n
+/// R = 2^k > n, values are kept as a R (mod n)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MontgomeryContext {
+    pub n: BigInt,
+    k: usize,
+    mask: BigInt,
+    /// -1 / n (mod R)
+    n_prime: BigInt,
+    /// R^2 (mod n)
+    r2: BigInt,
+}
+
+impl MontgomeryContext {
+    pub fn new(n: &BigInt) -> MontgomeryContext {
+        assert!(n.is_odd() && n > &One::one(), "modulus must be odd");
+        let k = n.bits();
+        let r = BigInt::one() << k;
+        let mask = &r - 1;
+        let n_prime = (&r - inverse_mod(n, &r).unwrap()).mod_floor(&r);
+        let r2 = (&r * &r).mod_floor(n);
+        MontgomeryContext { n: n.clone(), k, mask, n_prime, r2 }
+    }
+
+    /// t / R (mod n) for 0 <= t < n R
+    pub fn redc(&self, t: &BigInt) -> BigInt {
+        let m = ((t & &self.mask) * &self.n_prime) & &self.mask;
+        let u = (t + m * &self.n) >> self.k;
+        if u >= self.n {
+            u - &self.n
+        } else {
+            u
+        }
+    }
+
+    /// a -> a R (mod n)
+    pub fn to_montgomery(&self, a: &BigInt) -> BigInt {
+        self.redc(&(a.mod_floor(&self.n) * &self.r2))
+    }
+
+    /// a R -> a (mod n)
+    pub fn from_montgomery(&self, a: &BigInt) -> BigInt {
+        self.redc(a)
+    }
+
+    /// a R, b R -> a b R (mod n)
+    pub fn mul_montgomery(&self, a: &BigInt, b: &BigInt) -> BigInt {
+        self.redc(&(a * b))
+    }
+
+    /// a^e (mod n), e >= 0
+    pub fn power(&self, a: &BigInt, e: &BigInt) -> BigInt {
+        assert!(e >= &Zero::zero());
+        let b = self.to_montgomery(a);
+        let mut r = self.to_montgomery(&One::one());
+        for i in (0..e.bits()).rev() {
+            r = self.mul_montgomery(&r, &r);
+            if (e >> i).is_odd() {
+                r = self.mul_montgomery(&r, &b);
+            }
+        }
+        self.from_montgomery(&r)
+    }
+}
+
+impl Reduce for MontgomeryContext {
+    fn modulus(&self) -> &BigInt {
+        &self.n
+    }
+
+    /// x / R R^2 / R
+    fn reduce(&self, x: &BigInt) -> BigInt {
+        if x < &Zero::zero() || x.bits() > 2 * self.k {
+            return x.mod_floor(&self.n);
+        }
+        self.redc(&(self.redc(x) * &self.r2))
+    }
+
+    fn mul(&self, a: &BigInt, b: &BigInt) -> BigInt {
+        self.mul_montgomery(&self.mul_montgomery(a, b), &self.r2)
+    }
+}
+
+#[cfg(test)]
+fn check_reduce<R: Reduce>(ctx: &R) {
+    let n = ctx.modulus().clone();
+    let values = [
+        BigInt::zero(),
+        BigInt::one(),
+        &n - 1,
+        n.clone(),
+        &n * &n - 1,
+        -(&n * BigInt::from(3) + BigInt::from(5)),
+        BigInt::from(-1),
+        (&n * &n) << 3,
+    ];
+    for a in &values {
+        assert_eq!(ctx.reduce(a), a.mod_floor(&n), "{}", a);
+        for b in &values {
+            let a = a.mod_floor(&n);
+            let b = b.mod_floor(&n);
+            assert_eq!(ctx.mul(&a, &b), (&a * &b).mod_floor(&n));
+        }
+    }
+}
+
+#[test]
+fn modular_context_test() {
+    let p = BigInt::parse_bytes(b"FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F", 16).unwrap();
+    for n in &[p, BigInt::from(97), BigInt::from(1_000_001)] {
+        check_reduce(n);
+        check_reduce(&BarrettContext::new(n));
+        check_reduce(&MontgomeryContext::new(n));
+    }
+    // even modulus
+    check_reduce(&BarrettContext::new(&BigInt::from(1 << 20)));
+
+    let ctx = MontgomeryContext::new(&BigInt::from(101));
+    assert_eq_str!(ctx.power(&BigInt::from(3), &BigInt::from(100)), "1");
+    assert_eq_str!(ctx.power(&BigInt::from(2), &BigInt::from(7)), "27");
+    assert_eq_str!(ctx.from_montgomery(&ctx.to_montgomery(&BigInt::from(55))), "55");
+}
@@ -0,0 +1,384 @@
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+use num_traits::{Zero, One};
+use crate::bigint::{Inverse, Power, PowerModulo, RootModulo};
+use super::curve::Curve;
+use super::elliptic_curve::{EllipticCurve, ECPoint};
+
+/// B y^2 = x^3 + A x^2 + x
+/// GF(p)
+#[derive(Debug, Clone)]
+pub struct MontgomeryCurve {
+    pub a: BigInt,
+    pub b: BigInt,
+    pub p: BigInt,
+}
+
+/// projective x-coordinate (X : Z), x = X / Z
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XZPoint {
+    pub x: BigInt,
+    pub z: BigInt,
+}
+
+impl XZPoint {
+    pub fn new(x: &BigInt, z: &BigInt) -> XZPoint {
+        XZPoint {
+            x: x.clone(),
+            z: z.clone(),
+        }
+    }
+
+    pub fn infinity() -> XZPoint {
+        XZPoint {
+            x: One::one(),
+            z: Zero::zero(),
+        }
+    }
+
+    pub fn is_infinity(&self) -> bool {
+        self.z.is_zero()
+    }
+}
+
+impl MontgomeryCurve {
+    pub fn new(a: &BigInt, b: &BigInt, p: &BigInt) -> MontgomeryCurve {
+        let curve = MontgomeryCurve {
+            a: a.mod_floor(p),
+            b: b.mod_floor(p),
+            p: p.clone(),
+        };
+        assert!(!(&curve.b * (curve.a.power(2) - BigInt::from(4))).mod_floor(p).is_zero(), "singular curve");
+        curve
+    }
+
+    /// y^2 = x^3 + 486662 x^2 + x over GF(2^255 - 19)
+    pub fn curve25519() -> MontgomeryCurve {
+        let p = BigInt::from(2).power(255) - BigInt::from(19);
+        MontgomeryCurve::new(&BigInt::from(486_662), &One::one(), &p)
+    }
+
+    /// Montgomery curve isomorphic to y^2 = x^3 + a x + b
+    /// alpha: root of x^3 + a x + b with 3 alpha^2 + a a square
+    pub fn from_weierstrass(ec: &EllipticCurve, alpha: &BigInt) -> Option<MontgomeryCurve> {
+        let p = &ec.p;
+        if !(alpha.power(3) + &ec.a * alpha + &ec.b).mod_floor(p).is_zero() {
+            return None;
+        }
+        let s = (BigInt::from(3) * alpha.power(2) + &ec.a).sqrt_modulo(p)?;
+        if s.is_zero() {
+            return None;
+        }
+        let b = s.inverse(p);
+        let a = BigInt::from(3) * alpha * &b;
+        Some(MontgomeryCurve::new(&a, &b, p))
+    }
+
+    /// 256 (A^2 - 3)^3 / (A^2 - 4)
+    pub fn j_invariant(&self) -> BigInt {
+        let a2 = self.a.power(2);
+        let j = BigInt::from(256) * (&a2 - BigInt::from(3)).power(3) * (&a2 - BigInt::from(4)).inverse(&self.p);
+        j.mod_floor(&self.p)
+    }
+
+    pub fn is_on_curve(&self, point: &ECPoint) -> bool {
+        if point.is_infinity() {
+            return true;
+        }
+        let x = &point.x;
+        let lhs = &self.b * point.y.power(2);
+        let rhs = x.power(3) + &self.a * x.power(2) + x;
+        (lhs - rhs).mod_floor(&self.p).is_zero()
+    }
+
+    /// Point negation: -P
+    pub fn negate(&self, point: &ECPoint) -> ECPoint {
+        if point.is_infinity() {
+            return point.clone();
+        }
+        ECPoint::new(&point.x, &(-&point.y).mod_floor(&self.p), &point.z)
+    }
+
+    /// Elliptic curve point addition
+    pub fn plus(&self, point1: &ECPoint, point2: &ECPoint) -> ECPoint {
+        assert!(self.is_on_curve(point1), "point1 is not on curve");
+        assert!(self.is_on_curve(point2), "point2 is not on curve");
+        if point1.is_infinity() {
+            return point2.clone();
+        } else if point2.is_infinity() {
+            return point1.clone();
+        }
+        let p = &self.p;
+        let x1 = point1.x.mod_floor(p);
+        let y1 = point1.y.mod_floor(p);
+        let x2 = point2.x.mod_floor(p);
+        let y2 = point2.y.mod_floor(p);
+        let m = if x1 != x2 {
+            (&y2 - &y1) * (&x2 - &x1).inverse(p)
+        } else if y1 != y2 || y1.is_zero() {
+            return ECPoint::infinity();
+        } else {
+            (BigInt::from(3) * x1.power(2) + BigInt::from(2) * &self.a * &x1 + BigInt::from(1))
+                * (BigInt::from(2) * &self.b * &y1).inverse(p)
+        };
+        let x3 = &self.b * m.power(2) - &self.a - &x1 - &x2;
+        let y3 = m * (&x1 - &x3) - &y1;
+        ECPoint::new(&x3.mod_floor(p), &y3.mod_floor(p), &One::one())
+    }
+
+    /// rational points
+    pub fn points(&self) -> Vec<ECPoint> {
+        let mut points: Vec<ECPoint> = Vec::new();
+        for x in num_iter::range(BigInt::from(0), self.p.clone()) {
+            for y in num_iter::range(BigInt::from(0), self.p.clone()) {
+                let point = ECPoint::new(&x, &y, &One::one());
+                if self.is_on_curve(&point) {
+                    points.push(point);
+                }
+            }
+        }
+        points.push(ECPoint::infinity());
+        points
+    }
+
+    /// x(2 P)
+    pub fn x_double(&self, point: &XZPoint) -> XZPoint {
+        let p = &self.p;
+        let s = (&point.x + &point.z).power(2);
+        let d = (&point.x - &point.z).power(2);
+        // 4 X Z
+        let t = &s - &d;
+        // (4 s d : 4 t (d + (A + 2) t / 4))
+        let x = (BigInt::from(4) * &s * &d).mod_floor(p);
+        let z = (&t * (BigInt::from(4) * d + (&self.a + BigInt::from(2)) * &t)).mod_floor(p);
+        XZPoint::new(&x, &z)
+    }
+
+    /// x(P + Q) from x(P), x(Q) and x(P - Q)
+    pub fn x_add(&self, point1: &XZPoint, point2: &XZPoint, diff: &XZPoint) -> XZPoint {
+        let p = &self.p;
+        let u = (&point1.x - &point1.z) * (&point2.x + &point2.z);
+        let v = (&point1.x + &point1.z) * (&point2.x - &point2.z);
+        let x = (&diff.z * (&u + &v).power(2)).mod_floor(p);
+        let z = (&diff.x * (&u - &v).power(2)).mod_floor(p);
+        XZPoint::new(&x, &z)
+    }
+
+    /// x(n P) by the Montgomery ladder
+    pub fn ladder(&self, point: &XZPoint, n: &BigInt) -> XZPoint {
+        assert!(n >= &Zero::zero());
+        let mut r0 = XZPoint::infinity();
+        let mut r1 = point.clone();
+        for i in (0..n.bits()).rev() {
+            let bit = (n >> i).is_odd();
+            if bit {
+                r0 = self.x_add(&r1, &r0, point);
+                r1 = self.x_double(&r1);
+            } else {
+                r1 = self.x_add(&r1, &r0, point);
+                r0 = self.x_double(&r0);
+            }
+        }
+        r0
+    }
+
+    /// x(n P) for affine x, 0 for the point at infinity
+    pub fn ladder_x(&self, x: &BigInt, n: &BigInt) -> BigInt {
+        let r = self.ladder(&XZPoint::new(x, &One::one()), n);
+        // Z^(p - 2) = 0 for Z = 0
+        (&r.x * r.z.power_modulo(&(&self.p - BigInt::from(2)), &self.p)).mod_floor(&self.p)
+    }
+
+    /// y^2 = x^3 + a x + b
+    /// a = (3 - A^2) / 3 B^2, b = (2 A^3 - 9 A) / 27 B^3
+    pub fn to_weierstrass(&self) -> EllipticCurve {
+        let p = &self.p;
+        let a = (BigInt::from(3) - self.a.power(2)) * (BigInt::from(3) * self.b.power(2)).inverse(p);
+        let b = (BigInt::from(2) * self.a.power(3) - BigInt::from(9) * &self.a)
+            * (BigInt::from(27) * self.b.power(3)).inverse(p);
+        EllipticCurve::new_raw(&a.mod_floor(p), &b.mod_floor(p), p)
+    }
+
+    /// (u, v) -> (u / B + A / 3 B, v / B)
+    pub fn to_weierstrass_point(&self, point: &ECPoint) -> ECPoint {
+        if point.is_infinity() {
+            return ECPoint::infinity();
+        }
+        let p = &self.p;
+        let bi = self.b.inverse(p);
+        let x = (&point.x + &self.a * BigInt::from(3).inverse(p)) * &bi;
+        let y = &point.y * &bi;
+        ECPoint::new(&x.mod_floor(p), &y.mod_floor(p), &One::one())
+    }
+
+    /// (x, y) -> (B x - A / 3, B y)
+    pub fn from_weierstrass_point(&self, point: &ECPoint) -> ECPoint {
+        if point.is_infinity() {
+            return ECPoint::infinity();
+        }
+        let p = &self.p;
+        let u = &self.b * &point.x - &self.a * BigInt::from(3).inverse(p);
+        let v = &self.b * &point.y;
+        ECPoint::new(&u.mod_floor(p), &v.mod_floor(p), &One::one())
+    }
+}
+
+impl Curve for MontgomeryCurve {
+    type Point = ECPoint;
+
+    fn identity(&self) -> ECPoint {
+        ECPoint::infinity()
+    }
+
+    fn is_on_curve(&self, point: &ECPoint) -> bool {
+        MontgomeryCurve::is_on_curve(self, point)
+    }
+
+    fn negate(&self, point: &ECPoint) -> ECPoint {
+        MontgomeryCurve::negate(self, point)
+    }
+
+    fn plus(&self, point1: &ECPoint, point2: &ECPoint) -> ECPoint {
+        MontgomeryCurve::plus(self, point1, point2)
+    }
+
+    fn normalize(&self, point: &ECPoint) -> ECPoint {
+        if point.is_infinity() {
+            return ECPoint::infinity();
+        }
+        ECPoint::new(&point.x.mod_floor(&self.p), &point.y.mod_floor(&self.p), &One::one())
+    }
+}
+
+/// RFC 7748 X25519
+/// k: scalar, u: u-coordinate, both little endian
+pub fn x25519(k: &[u8; 32], u: &[u8; 32]) -> [u8; 32] {
+    let curve = MontgomeryCurve::curve25519();
+    let mut k = *k;
+    k[0] &= 248;
+    k[31] &= 127;
+    k[31] |= 64;
+    let mut u = *u;
+    u[31] &= 127;
+    let k = BigInt::from_bytes_le(Sign::Plus, &k);
+    let u = BigInt::from_bytes_le(Sign::Plus, &u).mod_floor(&curve.p);
+    let r = curve.ladder_x(&u, &k);
+    let (_, bytes) = r.to_bytes_le();
+    let mut out = [0u8; 32];
+    out[..bytes.len()].copy_from_slice(&bytes);
+    out
+}
+
+/// u = 9
+pub const X25519_BASEPOINT: [u8; 32] = [
+    9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+#[test]
+fn x25519_test() {
+    use super::hash::{from_hex, to_hex};
+    use std::convert::TryInto;
+    let key = |s: &str| -> [u8; 32] { from_hex(s).unwrap().try_into().unwrap() };
+    // RFC 7748 5.2
+    let out = x25519(
+        &key("a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4"),
+        &key("e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c"));
+    assert_eq!(to_hex(&out), "c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552");
+    let out = x25519(
+        &key("4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d"),
+        &key("e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493"));
+    assert_eq!(to_hex(&out), "95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957");
+    // one iteration
+    let out = x25519(&X25519_BASEPOINT, &X25519_BASEPOINT);
+    assert_eq!(to_hex(&out), "422c8e7a6227d7bca1350b3e2bb7279f7897b87bb6854b783c60e80311ae3079");
+}
+
+#[test]
+fn x25519_diffie_hellman_test() {
+    use super::hash::{from_hex, to_hex};
+    use std::convert::TryInto;
+    let key = |s: &str| -> [u8; 32] { from_hex(s).unwrap().try_into().unwrap() };
+    // RFC 7748 6.1
+    let alice = key("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+    let bob = key("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb");
+    let alice_public = x25519(&alice, &X25519_BASEPOINT);
+    let bob_public = x25519(&bob, &X25519_BASEPOINT);
+    assert_eq!(to_hex(&alice_public), "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a");
+    assert_eq!(to_hex(&bob_public), "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f");
+    let shared = "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742";
+    assert_eq!(to_hex(&x25519(&alice, &bob_public)), shared);
+    assert_eq!(to_hex(&x25519(&bob, &alice_public)), shared);
+}
+
+#[test]
+fn curve25519_test() {
+    let curve = MontgomeryCurve::curve25519();
+    // order of the base point u = 9
+    let l = BigInt::from(2).power(252)
+        + BigInt::parse_bytes(b"27742317777372353535851937790883648493", 10).unwrap();
+    assert!(curve.ladder(&XZPoint::new(&BigInt::from(9), &One::one()), &l).is_infinity());
+    assert_eq!(curve.j_invariant(), curve.to_weierstrass().j_invariant());
+    let ec = curve.to_weierstrass();
+    let v = ((BigInt::from(9).power(3) + &curve.a * BigInt::from(81) + BigInt::from(9)) * curve.b.inverse(&curve.p))
+        .sqrt_modulo(&curve.p).unwrap();
+    let g = ECPoint::new(&BigInt::from(9), &v, &One::one());
+    assert!(curve.is_on_curve(&g));
+    assert!(ec.is_on_curve(&curve.to_weierstrass_point(&g)));
+}
+
+#[test]
+fn montgomery_curve_test() {
+    let p = BigInt::from(101);
+    let curve = MontgomeryCurve::new(&BigInt::from(3), &BigInt::from(5), &p);
+    let ec = curve.to_weierstrass();
+    assert_eq!(curve.j_invariant(), ec.j_invariant());
+    let points = curve.points();
+    assert_eq!(points.len(), EllipticCurve::new(&ec.a, &ec.b, &p).cardinality());
+    for point1 in &points {
+        let image1 = curve.to_weierstrass_point(point1);
+        assert!(ec.is_on_curve(&image1));
+        assert_eq!(&curve.from_weierstrass_point(&image1), point1);
+        for point2 in points.iter().step_by(7) {
+            let image2 = curve.to_weierstrass_point(point2);
+            assert_eq!(curve.to_weierstrass_point(&curve.plus(point1, point2)), ec.plus(&image1, &image2));
+        }
+    }
+}
+
+#[test]
+fn montgomery_ladder_test() {
+    let p = BigInt::from(101);
+    let curve = MontgomeryCurve::new(&BigInt::from(3), &BigInt::from(5), &p);
+    for point in curve.points() {
+        if point.is_infinity() || point.x.is_zero() {
+            continue;
+        }
+        for n in 0..20 {
+            let n = BigInt::from(n);
+            let q = curve.multiply_scalar(&point, &n);
+            let r = curve.ladder(&XZPoint::new(&point.x, &One::one()), &n);
+            if q.is_infinity() {
+                assert!(r.is_infinity());
+            } else {
+                assert_eq!(curve.ladder_x(&point.x, &n), q.x);
+            }
+        }
+    }
+}
+
+#[test]
+fn montgomery_from_weierstrass_test() {
+    let p = BigInt::from(101);
+    let curve = MontgomeryCurve::new(&BigInt::from(3), &BigInt::from(5), &p);
+    let ec = curve.to_weierstrass();
+    // (0, 0) is a point of order 2
+    let alpha = curve.to_weierstrass_point(&ECPoint::new(&Zero::zero(), &Zero::zero(), &One::one())).x;
+    let curve2 = MontgomeryCurve::from_weierstrass(&ec, &alpha).unwrap();
+    // (A, B) up to sign
+    assert_eq!(curve2.a.power(2).mod_floor(&p), curve.a.power(2).mod_floor(&p));
+    assert_eq!((&curve2.a * &curve.b - &curve.a * &curve2.b).mod_floor(&p), BigInt::from(0));
+    assert_eq!(curve2.to_weierstrass().a, ec.a);
+    assert_eq!(curve2.to_weierstrass().b, ec.b);
+    assert!(MontgomeryCurve::from_weierstrass(&ec, &(alpha + 1)).is_none());
+}
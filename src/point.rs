@@ -0,0 +1,173 @@
+use num_bigint::BigInt;
+use std::rc::Rc;
+use std::{fmt, iter, ops};
+use super::elliptic_curve::{EllipticCurve, ECPoint};
+
+/// Rational point bound to its curve
+/// points share the curve through an Rc, combining points of different curves panics
+#[derive(Debug, Clone)]
+pub struct Point {
+    pub curve: Rc<EllipticCurve>,
+    pub point: ECPoint,
+}
+
+impl Point {
+    pub fn new(curve: &Rc<EllipticCurve>, point: &ECPoint) -> Point {
+        assert!(curve.is_on_curve(point), "point is not on curve");
+        Point {
+            curve: Rc::clone(curve),
+            point: curve.canonicalize(point),
+        }
+    }
+
+    pub fn infinity(curve: &Rc<EllipticCurve>) -> Point {
+        Point {
+            curve: Rc::clone(curve),
+            point: ECPoint::infinity(),
+        }
+    }
+
+    pub fn is_infinity(&self) -> bool {
+        self.point.is_infinity()
+    }
+
+    /// the same curve object, or one with the same a, b and p
+    pub fn is_same_curve(&self, other: &Point) -> bool {
+        let (c1, c2) = (&self.curve, &other.curve);
+        Rc::ptr_eq(c1, c2) || (c1.a == c2.a && c1.b == c2.b && c1.p == c2.p)
+    }
+
+    fn assert_same_curve(&self, other: &Point) {
+        assert!(self.is_same_curve(other), "points are on different curves");
+    }
+
+    fn with(&self, point: ECPoint) -> Point {
+        Point {
+            curve: Rc::clone(&self.curve),
+            point,
+        }
+    }
+
+    /// sum of a sequence of points on curve, O for an empty sequence
+    pub fn sum_on<I: IntoIterator<Item = Point>>(curve: &Rc<EllipticCurve>, iter: I) -> Point {
+        iter.into_iter().fold(Point::infinity(curve), |acc, point| acc + point)
+    }
+}
+
+impl EllipticCurve {
+    /// P as a point bound to this curve
+    pub fn point(self: &Rc<Self>, point: &ECPoint) -> Point {
+        Point::new(self, point)
+    }
+}
+
+impl PartialEq for Point {
+    fn eq(&self, other: &Point) -> bool {
+        self.is_same_curve(other) && self.point == other.point
+    }
+}
+
+impl Eq for Point {}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.point)
+    }
+}
+
+// Point + Point
+impl_op_ex!(+ |a: &Point, b: &Point| -> Point {
+    a.assert_same_curve(b);
+    a.with(a.curve.plus(&a.point, &b.point))
+});
+
+// Point += Point
+impl_op_ex!(+= |a: &mut Point, b: &Point| {
+    *a = &*a + b;
+});
+
+// Point - Point
+impl_op_ex!(- |a: &Point, b: &Point| -> Point {
+    a + (-b)
+});
+
+// Negate
+impl_op_ex!(- |a: &Point| -> Point {
+    a.with(a.curve.negate(&a.point))
+});
+
+// Point * BigInt
+impl_op_ex!(* |a: &Point, n: &BigInt| -> Point {
+    a.with(a.curve.multiply_scalar(&a.point, n))
+});
+
+// BigInt * Point
+impl_op_ex!(* |n: &BigInt, a: &Point| -> Point {
+    a * n
+});
+
+/// sum of a non-empty sequence, the curve is taken from the first point
+///
+/// # Panics
+///
+/// Panics on an empty sequence, use Point::sum_on to get O instead
+impl iter::Sum<Point> for Point {
+    fn sum<I: Iterator<Item = Point>>(mut iter: I) -> Point {
+        let first = iter.next().expect("sum of no points");
+        iter.fold(first, |acc, point| acc + point)
+    }
+}
+
+/// # Panics
+///
+/// Panics on an empty sequence, see iter::Sum<Point>
+impl<'a> iter::Sum<&'a Point> for Point {
+    fn sum<I: Iterator<Item = &'a Point>>(iter: I) -> Point {
+        iter.cloned().sum()
+    }
+}
+
+#[test]
+fn point_ops_test() {
+    let ec = Rc::new(EllipticCurve::new(&BigInt::from(2), &BigInt::from(3), &BigInt::from(97)));
+    let g = ec.point(&ECPoint::new(&BigInt::from(3), &BigInt::from(6), &BigInt::from(1)));
+    let k = BigInt::from(7);
+    let q = ec.point(&ec.multiply_scalar(&g.point, &BigInt::from(3)));
+    assert_eq!(&g * &k + &q, ec.point(&ec.multiply_scalar(&g.point, &BigInt::from(10))));
+    assert_eq!(&k * &g - &q, &g * &BigInt::from(4));
+    assert!((&g + &(-&g)).is_infinity());
+    assert_eq!(-(&g * &k), &g * &BigInt::from(-7));
+
+    let mut r = Point::infinity(&ec);
+    r += &g;
+    r += g.clone();
+    assert_eq!(r, &g * &BigInt::from(2));
+    let points = vec![g.clone(), q.clone(), -&g];
+    assert_eq!(points.iter().sum::<Point>(), q);
+    assert_eq!(Point::sum_on(&ec, points.clone()), q);
+    assert_eq!(points.into_iter().sum::<Point>(), q);
+    assert!(Point::sum_on(&ec, Vec::new()).is_infinity());
+    assert!((&g * &BigInt::from(5)).is_infinity());
+    assert_eq_str!(&g * &BigInt::from(2), "(80, 10)");
+
+    // an equal curve built separately
+    let other = Rc::new(EllipticCurve::new_raw(&ec.a, &ec.b, &ec.p));
+    assert_eq!(other.point(&g.point) + &g, &g * &BigInt::from(2));
+    assert!(Rc::ptr_eq(&(&g + &q).curve, &ec));
+}
+
+#[test]
+#[should_panic(expected = "points are on different curves")]
+fn point_different_curves_test() {
+    let ec1 = Rc::new(EllipticCurve::new(&BigInt::from(2), &BigInt::from(3), &BigInt::from(97)));
+    let ec2 = Rc::new(EllipticCurve::new(&BigInt::from(2), &BigInt::from(4), &BigInt::from(97)));
+    let p1 = ec1.point(&ECPoint::new(&BigInt::from(3), &BigInt::from(6), &BigInt::from(1)));
+    let p2 = Point::infinity(&ec2);
+    let _ = p1 + p2;
+}
+
+#[test]
+#[should_panic(expected = "sum of no points")]
+fn point_empty_sum_test() {
+    let _ = Vec::<Point>::new().into_iter().sum::<Point>();
+}
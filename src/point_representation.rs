@@ -0,0 +1,424 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Zero, One};
+use crate::bigint::{Inverse, Power};
+use super::elliptic_curve::{EllipticCurve, ECPoint};
+
+/// Coordinates of a point on y^2 = x^3 + a x + b
+/// each representation has its own group law formulas
+pub trait PointRepresentation: Clone + From<AffinePoint> {
+    fn identity() -> Self;
+
+    fn is_identity(&self, ec: &EllipticCurve) -> bool;
+
+    /// -P
+    fn negate(&self, ec: &EllipticCurve) -> Self;
+
+    /// 2 P
+    fn double(&self, ec: &EllipticCurve) -> Self;
+
+    /// P + Q
+    fn add(&self, other: &Self, ec: &EllipticCurve) -> Self;
+
+    fn to_affine(&self, ec: &EllipticCurve) -> AffinePoint;
+
+    /// n * P
+    fn multiply(&self, n: &BigInt, ec: &EllipticCurve) -> Self {
+        if n < &Zero::zero() {
+            return self.negate(ec).multiply(&(-n), ec);
+        }
+        let mut r = Self::identity();
+        for i in (0..n.bits()).rev() {
+            r = r.double(ec);
+            if (n >> i).is_odd() {
+                r = r.add(self, ec);
+            }
+        }
+        r
+    }
+}
+
+/// (x, y) or the point at infinity
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AffinePoint {
+    Identity,
+    Coordinates(BigInt, BigInt),
+}
+
+impl AffinePoint {
+    pub fn new(x: &BigInt, y: &BigInt) -> AffinePoint {
+        AffinePoint::Coordinates(x.clone(), y.clone())
+    }
+}
+
+/// x = X / Z^2, y = Y / Z^3
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JacobianPoint {
+    pub x: BigInt,
+    pub y: BigInt,
+    pub z: BigInt,
+}
+
+/// x = X / Z, y = Y / Z
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectivePoint {
+    pub x: BigInt,
+    pub y: BigInt,
+    pub z: BigInt,
+}
+
+/// Jacobian coordinates with Z^2 and Z^3 cached
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChudnovskyPoint {
+    pub x: BigInt,
+    pub y: BigInt,
+    pub z: BigInt,
+    pub z2: BigInt,
+    pub z3: BigInt,
+}
+
+impl From<AffinePoint> for ECPoint {
+    fn from(point: AffinePoint) -> ECPoint {
+        match point {
+            AffinePoint::Identity => ECPoint::infinity(),
+            AffinePoint::Coordinates(x, y) => ECPoint::new(&x, &y, &One::one()),
+        }
+    }
+}
+
+impl From<AffinePoint> for JacobianPoint {
+    fn from(point: AffinePoint) -> JacobianPoint {
+        match point {
+            AffinePoint::Identity => JacobianPoint::identity(),
+            AffinePoint::Coordinates(x, y) => JacobianPoint { x, y, z: One::one() },
+        }
+    }
+}
+
+/// ECPoint is read as Jacobian coordinates, z = 0 is the point at infinity
+impl From<ECPoint> for JacobianPoint {
+    fn from(point: ECPoint) -> JacobianPoint {
+        if point.is_infinity() {
+            return JacobianPoint::identity();
+        }
+        JacobianPoint { x: point.x, y: point.y, z: point.z }
+    }
+}
+
+impl From<JacobianPoint> for ECPoint {
+    fn from(point: JacobianPoint) -> ECPoint {
+        if point.z.is_zero() {
+            return ECPoint::infinity();
+        }
+        ECPoint::new(&point.x, &point.y, &point.z)
+    }
+}
+
+impl From<AffinePoint> for ProjectivePoint {
+    fn from(point: AffinePoint) -> ProjectivePoint {
+        match point {
+            AffinePoint::Identity => ProjectivePoint::identity(),
+            AffinePoint::Coordinates(x, y) => ProjectivePoint { x, y, z: One::one() },
+        }
+    }
+}
+
+impl From<AffinePoint> for ChudnovskyPoint {
+    fn from(point: AffinePoint) -> ChudnovskyPoint {
+        ChudnovskyPoint::from(JacobianPoint::from(point))
+    }
+}
+
+impl From<JacobianPoint> for ChudnovskyPoint {
+    fn from(point: JacobianPoint) -> ChudnovskyPoint {
+        let z2 = point.z.power(2);
+        let z3 = &z2 * &point.z;
+        ChudnovskyPoint { x: point.x, y: point.y, z: point.z, z2, z3 }
+    }
+}
+
+impl From<ChudnovskyPoint> for JacobianPoint {
+    fn from(point: ChudnovskyPoint) -> JacobianPoint {
+        JacobianPoint { x: point.x, y: point.y, z: point.z }
+    }
+}
+
+impl PointRepresentation for AffinePoint {
+    fn identity() -> AffinePoint {
+        AffinePoint::Identity
+    }
+
+    fn is_identity(&self, _ec: &EllipticCurve) -> bool {
+        self == &AffinePoint::Identity
+    }
+
+    fn negate(&self, ec: &EllipticCurve) -> AffinePoint {
+        match self {
+            AffinePoint::Identity => AffinePoint::Identity,
+            AffinePoint::Coordinates(x, y) => AffinePoint::new(x, &(-y).mod_floor(&ec.p)),
+        }
+    }
+
+    fn double(&self, ec: &EllipticCurve) -> AffinePoint {
+        self.add(self, ec)
+    }
+
+    /// chord and tangent, one inversion
+    fn add(&self, other: &AffinePoint, ec: &EllipticCurve) -> AffinePoint {
+        let p = &ec.p;
+        let (x1, y1, x2, y2) = match (self, other) {
+            (AffinePoint::Identity, _) => return other.clone(),
+            (_, AffinePoint::Identity) => return self.clone(),
+            (AffinePoint::Coordinates(x1, y1), AffinePoint::Coordinates(x2, y2)) => (x1, y1, x2, y2),
+        };
+        let m = if (x1 - x2).mod_floor(p).is_zero() {
+            if !(y1 + y2).mod_floor(p).is_zero() {
+                (BigInt::from(3) * x1.power(2) + &ec.a) * (BigInt::from(2) * y1).inverse(p)
+            } else {
+                return AffinePoint::Identity;
+            }
+        } else {
+            (y2 - y1) * (x2 - x1).inverse(p)
+        };
+        let x3 = (m.power(2) - x1 - x2).mod_floor(p);
+        let y3 = (m * (x1 - &x3) - y1).mod_floor(p);
+        AffinePoint::Coordinates(x3, y3)
+    }
+
+    fn to_affine(&self, ec: &EllipticCurve) -> AffinePoint {
+        match self {
+            AffinePoint::Identity => AffinePoint::Identity,
+            AffinePoint::Coordinates(x, y) => AffinePoint::new(&x.mod_floor(&ec.p), &y.mod_floor(&ec.p)),
+        }
+    }
+}
+
+impl PointRepresentation for JacobianPoint {
+    /// (1 : 1 : 0)
+    fn identity() -> JacobianPoint {
+        JacobianPoint { x: One::one(), y: One::one(), z: Zero::zero() }
+    }
+
+    fn is_identity(&self, ec: &EllipticCurve) -> bool {
+        self.z.mod_floor(&ec.p).is_zero()
+    }
+
+    fn negate(&self, ec: &EllipticCurve) -> JacobianPoint {
+        JacobianPoint { x: self.x.clone(), y: (-&self.y).mod_floor(&ec.p), z: self.z.clone() }
+    }
+
+    /// S = 4 X Y^2, M = 3 X^2 + a Z^4
+    /// X3 = M^2 - 2 S, Y3 = M (S - X3) - 8 Y^4, Z3 = 2 Y Z
+    fn double(&self, ec: &EllipticCurve) -> JacobianPoint {
+        let p = &ec.p;
+        if self.is_identity(ec) {
+            return self.clone();
+        }
+        let y2 = self.y.power(2);
+        let s = BigInt::from(4) * &self.x * &y2;
+        let m = BigInt::from(3) * self.x.power(2) + &ec.a * self.z.power(4);
+        let x3 = (m.power(2) - BigInt::from(2) * &s).mod_floor(p);
+        let y3 = (m * (&s - &x3) - BigInt::from(8) * y2.power(2)).mod_floor(p);
+        let z3 = (BigInt::from(2) * &self.y * &self.z).mod_floor(p);
+        JacobianPoint { x: x3, y: y3, z: z3 }
+    }
+
+    /// U1 = X1 Z2^2, U2 = X2 Z1^2, S1 = Y1 Z2^3, S2 = Y2 Z1^3, H = U2 - U1, R = S2 - S1
+    /// X3 = R^2 - H^3 - 2 U1 H^2, Y3 = R (U1 H^2 - X3) - S1 H^3, Z3 = H Z1 Z2
+    fn add(&self, other: &JacobianPoint, ec: &EllipticCurve) -> JacobianPoint {
+        if self.is_identity(ec) {
+            return other.clone();
+        } else if other.is_identity(ec) {
+            return self.clone();
+        }
+        let z1z1 = self.z.power(2);
+        let z2z2 = other.z.power(2);
+        jacobian_add(self, other, &z1z1, &(&z1z1 * &self.z), &z2z2, &(&z2z2 * &other.z), ec)
+    }
+
+    fn to_affine(&self, ec: &EllipticCurve) -> AffinePoint {
+        let p = &ec.p;
+        if self.is_identity(ec) {
+            return AffinePoint::Identity;
+        }
+        let zi = self.z.inverse(p);
+        let zi2 = zi.power(2);
+        let x = (&self.x * &zi2).mod_floor(p);
+        let y = (&self.y * &zi2 * &zi).mod_floor(p);
+        AffinePoint::Coordinates(x, y)
+    }
+}
+
+/// Jacobian addition with Z1^2, Z1^3, Z2^2, Z2^3 given
+fn jacobian_add(point1: &JacobianPoint, point2: &JacobianPoint,
+    z1z1: &BigInt, z1z1z1: &BigInt, z2z2: &BigInt, z2z2z2: &BigInt, ec: &EllipticCurve) -> JacobianPoint {
+    let p = &ec.p;
+    let u1 = (&point1.x * z2z2).mod_floor(p);
+    let u2 = (&point2.x * z1z1).mod_floor(p);
+    let s1 = (&point1.y * z2z2z2).mod_floor(p);
+    let s2 = (&point2.y * z1z1z1).mod_floor(p);
+    let h = (&u2 - &u1).mod_floor(p);
+    let r = (&s2 - &s1).mod_floor(p);
+    if h.is_zero() {
+        if r.is_zero() {
+            return point1.double(ec);
+        }
+        return JacobianPoint::identity();
+    }
+    let h2 = h.power(2);
+    let h3 = &h2 * &h;
+    let u1h2 = &u1 * &h2;
+    let x3 = (r.power(2) - &h3 - BigInt::from(2) * &u1h2).mod_floor(p);
+    let y3 = (r * (&u1h2 - &x3) - s1 * h3).mod_floor(p);
+    let z3 = (h * &point1.z * &point2.z).mod_floor(p);
+    JacobianPoint { x: x3, y: y3, z: z3 }
+}
+
+impl PointRepresentation for ProjectivePoint {
+    /// (0 : 1 : 0)
+    fn identity() -> ProjectivePoint {
+        ProjectivePoint { x: Zero::zero(), y: One::one(), z: Zero::zero() }
+    }
+
+    fn is_identity(&self, ec: &EllipticCurve) -> bool {
+        self.z.mod_floor(&ec.p).is_zero()
+    }
+
+    fn negate(&self, ec: &EllipticCurve) -> ProjectivePoint {
+        ProjectivePoint { x: self.x.clone(), y: (-&self.y).mod_floor(&ec.p), z: self.z.clone() }
+    }
+
+    /// w = a Z^2 + 3 X^2, s = Y Z, B = X Y s, h = w^2 - 8 B
+    /// X3 = 2 h s, Y3 = w (4 B - h) - 8 Y^2 s^2, Z3 = 8 s^3
+    fn double(&self, ec: &EllipticCurve) -> ProjectivePoint {
+        let p = &ec.p;
+        if self.is_identity(ec) {
+            return self.clone();
+        }
+        let w = &ec.a * self.z.power(2) + BigInt::from(3) * self.x.power(2);
+        let s = &self.y * &self.z;
+        let b = &self.x * &self.y * &s;
+        let h = w.power(2) - BigInt::from(8) * &b;
+        let x3 = (BigInt::from(2) * &h * &s).mod_floor(p);
+        let y3 = (w * (BigInt::from(4) * &b - &h) - BigInt::from(8) * self.y.power(2) * s.power(2)).mod_floor(p);
+        let z3 = (BigInt::from(8) * s.power(3)).mod_floor(p);
+        ProjectivePoint { x: x3, y: y3, z: z3 }
+    }
+
+    /// u = Y2 Z1 - Y1 Z2, v = X2 Z1 - X1 Z2, w = u^2 Z1 Z2 - v^3 - 2 v^2 X1 Z2
+    /// X3 = v w, Y3 = u (v^2 X1 Z2 - w) - v^3 Y1 Z2, Z3 = v^3 Z1 Z2
+    fn add(&self, other: &ProjectivePoint, ec: &EllipticCurve) -> ProjectivePoint {
+        let p = &ec.p;
+        if self.is_identity(ec) {
+            return other.clone();
+        } else if other.is_identity(ec) {
+            return self.clone();
+        }
+        let u = (&other.y * &self.z - &self.y * &other.z).mod_floor(p);
+        let v = (&other.x * &self.z - &self.x * &other.z).mod_floor(p);
+        if v.is_zero() {
+            if u.is_zero() {
+                return self.double(ec);
+            }
+            return ProjectivePoint::identity();
+        }
+        let zz = &self.z * &other.z;
+        let v2 = v.power(2);
+        let v3 = &v2 * &v;
+        let v2x1z2 = &v2 * &self.x * &other.z;
+        let w = u.power(2) * &zz - &v3 - BigInt::from(2) * &v2x1z2;
+        let x3 = (&v * &w).mod_floor(p);
+        let y3 = (u * (v2x1z2 - &w) - &v3 * &self.y * &other.z).mod_floor(p);
+        let z3 = (v3 * zz).mod_floor(p);
+        ProjectivePoint { x: x3, y: y3, z: z3 }
+    }
+
+    fn to_affine(&self, ec: &EllipticCurve) -> AffinePoint {
+        let p = &ec.p;
+        if self.is_identity(ec) {
+            return AffinePoint::Identity;
+        }
+        let zi = self.z.inverse(p);
+        AffinePoint::Coordinates((&self.x * &zi).mod_floor(p), (&self.y * &zi).mod_floor(p))
+    }
+}
+
+impl PointRepresentation for ChudnovskyPoint {
+    fn identity() -> ChudnovskyPoint {
+        ChudnovskyPoint::from(JacobianPoint::identity())
+    }
+
+    fn is_identity(&self, ec: &EllipticCurve) -> bool {
+        self.z.mod_floor(&ec.p).is_zero()
+    }
+
+    fn negate(&self, ec: &EllipticCurve) -> ChudnovskyPoint {
+        let mut point = self.clone();
+        point.y = (-&self.y).mod_floor(&ec.p);
+        point
+    }
+
+    fn double(&self, ec: &EllipticCurve) -> ChudnovskyPoint {
+        let point = JacobianPoint::from(self.clone()).double(ec);
+        ChudnovskyPoint::from(point)
+    }
+
+    /// Jacobian addition without recomputing Z^2, Z^3
+    fn add(&self, other: &ChudnovskyPoint, ec: &EllipticCurve) -> ChudnovskyPoint {
+        if self.is_identity(ec) {
+            return other.clone();
+        } else if other.is_identity(ec) {
+            return self.clone();
+        }
+        let point1 = JacobianPoint::from(self.clone());
+        let point2 = JacobianPoint::from(other.clone());
+        let point = jacobian_add(&point1, &point2, &self.z2, &self.z3, &other.z2, &other.z3, ec);
+        ChudnovskyPoint::from(point)
+    }
+
+    fn to_affine(&self, ec: &EllipticCurve) -> AffinePoint {
+        JacobianPoint::from(self.clone()).to_affine(ec)
+    }
+}
+
+#[cfg(test)]
+fn check_representation<P: PointRepresentation>(ec: &EllipticCurve) {
+    let affine = |point: &ECPoint| -> AffinePoint {
+        if point.is_infinity() {
+            AffinePoint::Identity
+        } else {
+            AffinePoint::new(&point.x, &point.y)
+        }
+    };
+    let points = ec.points();
+    for point1 in &points {
+        let r1 = P::from(affine(point1));
+        assert_eq!(r1.double(ec).to_affine(ec), affine(&ec.plus(point1, point1)));
+        assert_eq!(r1.negate(ec).to_affine(ec), affine(&ec.negate(point1)));
+        let n = BigInt::from(13);
+        assert_eq!(r1.multiply(&n, ec).to_affine(ec), affine(&ec.multiply_scalar(point1, &n)));
+        for point2 in &points {
+            let r2 = P::from(affine(point2));
+            // go through a non-trivial Z
+            let r2 = r2.double(ec).add(&r2.negate(ec), ec);
+            assert_eq!(r1.add(&r2, ec).to_affine(ec), affine(&ec.plus(point1, point2)));
+        }
+    }
+}
+
+#[test]
+fn point_representation_test() {
+    let ec = EllipticCurve::new(&BigInt::from(2), &BigInt::from(3), &BigInt::from(31));
+    check_representation::<AffinePoint>(&ec);
+    check_representation::<JacobianPoint>(&ec);
+    check_representation::<ProjectivePoint>(&ec);
+    check_representation::<ChudnovskyPoint>(&ec);
+
+    let point = AffinePoint::new(&BigInt::from(3), &BigInt::from(25));
+    let jacobian = JacobianPoint::from(point.clone()).double(&ec);
+    let chudnovsky = ChudnovskyPoint::from(jacobian.clone());
+    assert_eq!(chudnovsky.to_affine(&ec), jacobian.to_affine(&ec));
+    assert_eq!(ECPoint::from(AffinePoint::Identity), ECPoint::infinity());
+    assert!(JacobianPoint::from(ECPoint::infinity()).is_identity(&ec));
+    assert_eq_str!(ECPoint::from(point), "(3, 25)");
+}
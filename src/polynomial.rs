@@ -4,7 +4,8 @@ use num_traits::{Zero, One};
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::{fmt, ops};
-use super::bigint::{Inverse, Power};
+use super::bigint::{inverse_mod, Inverse, Power};
+use super::modular::{BarrettContext, Reduce};
 use super::term;
 use super::term_builder::TermBuildable;
 use super::term_builder;
@@ -319,22 +320,28 @@ impl Polynomial {
         self.power(3)
     }
 
+    /// convenience for a one-off p, builds a BarrettContext on each call
     pub fn power_modulo(&self, n: &BigInt, p: &BigInt) -> Self {
+        self.power_modulo_with(n, &BarrettContext::new(p))
+    }
+
+    /// self^n (mod ctx.n) with a context reused across calls
+    pub fn power_modulo_with(&self, n: &BigInt, ctx: &BarrettContext) -> Self {
         assert!(*n >= Zero::zero());
-        let mut b = self % p;
+        let mut b = self % &ctx.n;
         let mut r: Polynomial = One::one();
         let mut e = n.clone();
         while &e > &One::one() {
             if e.is_odd() {
                 r *= &b;
-                r.modular_assign(p);
+                r.modular_assign(ctx);
             }
             b *= b.clone();
-            b.modular_assign(p);
+            b.modular_assign(ctx);
             e /= 2;
         }
         r *= b;
-        r.modular_assign(p);
+        r.modular_assign(ctx);
         r
     }
 
@@ -349,9 +356,16 @@ impl Polynomial {
         pol
     }
 
+    /// convenience for a one-off p, builds a BarrettContext on each call
     pub fn polynomial_modular(&self, other: &Polynomial, p: &BigInt) -> Self {
+        self.polynomial_modular_with(other, &BarrettContext::new(p))
+    }
+
+    /// self (mod other) with coefficients modulo ctx.n, the context is reused across calls
+    pub fn polynomial_modular_with(&self, other: &Polynomial, ctx: &BarrettContext) -> Self {
         assert!(!other.has_y(), "!other.has_y()");
         assert!(!other.has_q(), "!other.has_q()");
+        let p = &ctx.n;
         let oh = other.highest_term_x();
         let mut r = self.clone();
         loop {
@@ -360,28 +374,30 @@ impl Polynomial {
                 break;
             }
             let mut q = term_builder::TermBuilder::new()
-                    .coef(&rh.coef * oh.coef.inverse(p))
+                    .coef(&rh.coef * inverse_mod(&oh.coef, p).expect("leading coefficient is not invertible"))
                     .xpow(rh.xpow() - oh.xpow())
                     .ypow(rh.ypow())
                     .qpow(rh.qpow())
                     .build();
-            q.modular_assign(&p);
+            q.modular_assign(p);
             let mut d = q * other;
 
-            d.modular_assign(&p);
+            d.modular_assign(ctx);
             r -= d;
-            r.modular_assign(&p);
+            r.modular_assign(ctx);
         }
-        r.modular_assign(&p);
+        r.modular_assign(ctx);
         r
     }
 
-    pub fn modular_assign(&mut self, p: &BigInt) {
+    /// reduce coefficients modulo p, p is a BigInt or a precomputed context
+    pub fn modular_assign<R: Reduce + ?Sized>(&mut self, p: &R) {
+        let modulus = p.modulus();
         let mut del: BTreeSet<term::Monomial> = BTreeSet::new();
         for (m, coef) in &mut self.terms {
-            let c = coef.mod_floor(p);
-            if p != &Zero::zero() {
-                assert!(&c < p, "c {}", &c);
+            let c = p.reduce(coef);
+            if !modulus.is_zero() {
+                assert!(&c < modulus, "c {}", &c);
             }
             *coef = c.clone();
             if c.is_zero() {
@@ -398,8 +414,8 @@ impl Polynomial {
 
         // check
         for (_, coef) in &self.terms {
-            if p != &Zero::zero() {
-                assert!(coef < p, "{} {}", &coef, self.to_string());
+            if !modulus.is_zero() {
+                assert!(coef < modulus, "{} {}", &coef, self);
             }
         }
     }
@@ -442,16 +458,20 @@ impl Polynomial {
     }
 
     pub fn gcd(&self, other: &Self, p: &BigInt) -> Polynomial {
+        self.gcd_with(other, &BarrettContext::new(p))
+    }
+
+    fn gcd_with(&self, other: &Self, ctx: &BarrettContext) -> Polynomial {
         let s = self.highest_term_x();
         let o = other.highest_term_x();
         if s.xpow() < o.xpow() {
-            return other.gcd(self, p);
+            return other.gcd_with(self, ctx);
         }
-        let r = self.polynomial_modular(other, p);
+        let r = self.polynomial_modular_with(other, ctx);
         if r.is_zero() {
-            return other.to_monic(p);
+            return other.to_monic(&ctx.n);
         }
-        return other.gcd(&r, p);
+        other.gcd_with(&r, ctx)
     }
 
     pub fn to_monic(&self, p: &BigInt) -> Polynomial {
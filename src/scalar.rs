@@ -0,0 +1,155 @@
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+use rand::Rng;
+use std::fmt;
+use crate::bigint::inverse_mod;
+use super::u256::U256;
+
+/// n, order of the secp256k1 generator
+pub const SECP256K1_N: U256 = U256([
+    0xBFD2_5E8C_D036_4141,
+    0xBAAE_DCE6_AF48_A03B,
+    0xFFFF_FFFF_FFFF_FFFE,
+    0xFFFF_FFFF_FFFF_FFFF,
+]);
+
+/// h = #E / n
+pub const SECP256K1_H: u64 = 1;
+
+/// Element of Z/nZ for the secp256k1 group order n, always reduced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Scalar(U256);
+
+impl Scalar {
+    pub const ZERO: Scalar = Scalar(U256::ZERO);
+    pub const ONE: Scalar = Scalar(U256::ONE);
+
+    pub fn order() -> BigInt {
+        SECP256K1_N.to_bigint()
+    }
+
+    pub fn new(n: &U256) -> Scalar {
+        if n >= &SECP256K1_N {
+            Scalar(n.overflowing_sub(&SECP256K1_N).0)
+        } else {
+            Scalar(*n)
+        }
+    }
+
+    pub fn from_bigint(n: &BigInt) -> Scalar {
+        Scalar(U256::from_bigint(&n.mod_floor(&Scalar::order())))
+    }
+
+    pub fn from_u64(n: u64) -> Scalar {
+        Scalar(U256([n, 0, 0, 0]))
+    }
+
+    /// big-endian bytes of any length, e.g. a 512-bit hash, reduced mod n
+    pub fn from_bytes_reduced(bytes: &[u8]) -> Scalar {
+        Scalar::from_bigint(&BigInt::from_bytes_be(Sign::Plus, bytes))
+    }
+
+    /// 32 big-endian bytes, None unless 0 <= value < n
+    pub fn from_be_bytes(bytes: &[u8; 32]) -> Option<Scalar> {
+        let n = U256::from_be_bytes(bytes);
+        if n < SECP256K1_N {
+            Some(Scalar(n))
+        } else {
+            None
+        }
+    }
+
+    /// uniform in [1, n)
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Scalar {
+        loop {
+            let bytes: [u8; 32] = rng.gen();
+            match Scalar::from_be_bytes(&bytes) {
+                Some(k) if !k.is_zero() => return k,
+                _ => continue,
+            }
+        }
+    }
+
+    pub fn to_u256(&self) -> U256 {
+        self.0
+    }
+
+    pub fn to_bigint(&self) -> BigInt {
+        self.0.to_bigint()
+    }
+
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        self.0.to_be_bytes()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    pub fn add(&self, other: &Scalar) -> Scalar {
+        let (s, carry) = self.0.overflowing_add(&other.0);
+        if carry || s >= SECP256K1_N {
+            Scalar(s.overflowing_sub(&SECP256K1_N).0)
+        } else {
+            Scalar(s)
+        }
+    }
+
+    pub fn sub(&self, other: &Scalar) -> Scalar {
+        let (d, borrow) = self.0.overflowing_sub(&other.0);
+        if borrow {
+            Scalar(d.overflowing_add(&SECP256K1_N).0)
+        } else {
+            Scalar(d)
+        }
+    }
+
+    pub fn neg(&self) -> Scalar {
+        Scalar::ZERO.sub(self)
+    }
+
+    pub fn mul(&self, other: &Scalar) -> Scalar {
+        Scalar::from_bigint(&(self.to_bigint() * other.to_bigint()))
+    }
+
+    /// 1 / self (mod n)
+    pub fn inverse(&self) -> Scalar {
+        let inv = inverse_mod(&self.to_bigint(), &Scalar::order()).expect("inverse of zero");
+        Scalar::from_bigint(&inv)
+    }
+}
+
+impl fmt::Display for Scalar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[test]
+fn scalar_test() {
+    use rand::{SeedableRng, rngs::StdRng};
+    let n = Scalar::order();
+    assert_eq_str!(n, "115792089237316195423570985008687907852837564279074904382605163141518161494337");
+    let a = Scalar::from_bigint(&(&n + BigInt::from(5)));
+    assert_eq_str!(a, "5");
+    assert_eq!(Scalar::from_bigint(&BigInt::from(-1)).to_bigint(), &n - 1);
+    assert_eq!(a.add(&a.neg()), Scalar::ZERO);
+    assert_eq!(a.sub(&Scalar::from_u64(7)).to_bigint(), &n - 2);
+    assert_eq!(a.mul(&a.inverse()), Scalar::ONE);
+    assert_eq!(Scalar::ZERO.neg(), Scalar::ZERO);
+    assert!(Scalar::from_be_bytes(&SECP256K1_N.to_be_bytes()).is_none());
+
+    // 512-bit hash
+    let wide = [0xffu8; 64];
+    let expected: BigInt = (BigInt::from(1) << 512) - 1;
+    assert_eq!(Scalar::from_bytes_reduced(&wide).to_bigint(), expected.mod_floor(&n));
+
+    let mut rng = StdRng::seed_from_u64(39);
+    for _ in 0..10 {
+        let k = Scalar::random(&mut rng);
+        assert!(!k.is_zero() && k.to_bigint() < n);
+        let l = Scalar::random(&mut rng);
+        assert_eq!(k.mul(&l).to_bigint(), (k.to_bigint() * l.to_bigint()).mod_floor(&n));
+        assert_eq!(k.add(&l).to_bigint(), (k.to_bigint() + l.to_bigint()).mod_floor(&n));
+    }
+}
@@ -0,0 +1,191 @@
+use super::hash::tagged_hash;
+use super::scalar::Scalar;
+use super::secp256k1::{Secp256k1, Secp256k1Point};
+use super::u256::FieldElement;
+
+/// x coordinate of a point with even y, BIP-340
+pub type XOnlyPublicKey = [u8; 32];
+
+/// R.x || s, BIP-340
+pub type SchnorrSignature = [u8; 64];
+
+/// x coordinate and y parity of a finite point
+fn x_and_parity(point: &Secp256k1Point) -> ([u8; 32], bool) {
+    let (x, y) = point.to_affine().expect("point at infinity");
+    (x.to_be_bytes(), y.is_odd())
+}
+
+/// scalar of a 32-byte hash, reduced mod n
+fn hash_to_scalar(hash: &[u8; 32]) -> Scalar {
+    Scalar::from_bytes_reduced(hash)
+}
+
+/// compact size prefix of Bitcoin serialization
+fn compact_size(n: usize) -> Vec<u8> {
+    match n {
+        0..=0xfc => vec![n as u8],
+        0xfd..=0xffff => [&[0xfd], &(n as u16).to_le_bytes()[..]].concat(),
+        0x1_0000..=0xffff_ffff => [&[0xfe], &(n as u32).to_le_bytes()[..]].concat(),
+        _ => [&[0xff], &(n as u64).to_le_bytes()[..]].concat(),
+    }
+}
+
+/// tagged_hash("TapLeaf", version || compact_size(script) || script), BIP-341
+pub fn tap_leaf_hash(version: u8, script: &[u8]) -> [u8; 32] {
+    tagged_hash("TapLeaf", &[&[version], &compact_size(script.len())[..], script].concat())
+}
+
+/// tagged_hash("TapBranch", min(a, b) || max(a, b)), BIP-341
+pub fn tap_branch_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    tagged_hash("TapBranch", &[&lo[..], &hi[..]].concat())
+}
+
+impl Secp256k1 {
+    /// P = lift_x(x), None unless x < p is the x coordinate of a point
+    pub fn lift_x(&self, x: &XOnlyPublicKey) -> Option<Secp256k1Point> {
+        Secp256k1Point::lift_x(&FieldElement::from_be_bytes(x)?)
+    }
+
+    /// x(d G)
+    pub fn x_only_public_key(&self, secret_key: &Scalar) -> XOnlyPublicKey {
+        assert!(!secret_key.is_zero(), "secret key is zero");
+        x_and_parity(&self.generator().multiply(secret_key)).0
+    }
+
+    /// BIP-340 signature of msg with auxiliary randomness aux_rand
+    pub fn schnorr_sign(&self, secret_key: &Scalar, msg: &[u8], aux_rand: &[u8; 32]) -> SchnorrSignature {
+        assert!(!secret_key.is_zero(), "secret key is zero");
+        let (p, odd) = x_and_parity(&self.generator().multiply(secret_key));
+        let d = if odd { secret_key.neg() } else { *secret_key };
+        let aux = tagged_hash("BIP0340/aux", aux_rand);
+        let mut t = d.to_be_bytes();
+        for (ti, ai) in t.iter_mut().zip(aux.iter()) {
+            *ti ^= ai;
+        }
+        let rand = tagged_hash("BIP0340/nonce", &[&t[..], &p, msg].concat());
+        let k0 = hash_to_scalar(&rand);
+        assert!(!k0.is_zero(), "nonce is zero");
+        let (r, odd) = x_and_parity(&self.generator().multiply(&k0));
+        let k = if odd { k0.neg() } else { k0 };
+        let e = hash_to_scalar(&tagged_hash("BIP0340/challenge", &[&r[..], &p, msg].concat()));
+        let s = k.add(&e.mul(&d));
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(&r);
+        signature[32..].copy_from_slice(&s.to_be_bytes());
+        signature
+    }
+
+    /// BIP-340 verification, R = s G - e P must have even y and x(R) = r
+    pub fn schnorr_verify(&self, public_key: &XOnlyPublicKey, msg: &[u8], signature: &SchnorrSignature) -> bool {
+        let p = match self.lift_x(public_key) {
+            Some(p) => p,
+            None => return false,
+        };
+        let mut r = [0u8; 32];
+        r.copy_from_slice(&signature[..32]);
+        let mut s = [0u8; 32];
+        s.copy_from_slice(&signature[32..]);
+        let (r, s) = match (FieldElement::from_be_bytes(&r), Scalar::from_be_bytes(&s)) {
+            (Some(r), Some(s)) => (r, s),
+            _ => return false,
+        };
+        let e = hash_to_scalar(&tagged_hash("BIP0340/challenge", &[&signature[..32], &public_key[..], msg].concat()));
+        let point = self.generator().multiply(&s).add(&p.multiply(&e.neg()));
+        match point.to_affine() {
+            Some((x, y)) => !y.is_odd() && x == r,
+            None => false,
+        }
+    }
+
+    /// t = tagged_hash("TapTweak", P || merkle_root) as a scalar, None if t >= n
+    fn taproot_tweak(public_key: &XOnlyPublicKey, merkle_root: Option<&[u8; 32]>) -> Option<Scalar> {
+        let data = match merkle_root {
+            Some(root) => [&public_key[..], &root[..]].concat(),
+            None => public_key.to_vec(),
+        };
+        Scalar::from_be_bytes(&tagged_hash("TapTweak", &data))
+    }
+
+    /// Q = P + t G, BIP-341 output key and the parity of its y coordinate
+    pub fn taproot_tweak_public_key(&self, public_key: &XOnlyPublicKey, merkle_root: Option<&[u8; 32]>)
+        -> Option<(XOnlyPublicKey, bool)> {
+        let p = self.lift_x(public_key)?;
+        let t = Secp256k1::taproot_tweak(public_key, merkle_root)?;
+        let q = p.add(&self.generator().multiply(&t));
+        if q.is_infinity() {
+            return None;
+        }
+        Some(x_and_parity(&q))
+    }
+
+    /// secret key of the BIP-341 output key, d' + t with d' negated for odd y
+    pub fn taproot_tweak_secret_key(&self, secret_key: &Scalar, merkle_root: Option<&[u8; 32]>) -> Option<Scalar> {
+        assert!(!secret_key.is_zero(), "secret key is zero");
+        let (p, odd) = x_and_parity(&self.generator().multiply(secret_key));
+        let d = if odd { secret_key.neg() } else { *secret_key };
+        let t = Secp256k1::taproot_tweak(&p, merkle_root)?;
+        let tweaked = d.add(&t);
+        if tweaked.is_zero() {
+            None
+        } else {
+            Some(tweaked)
+        }
+    }
+}
+
+#[cfg(test)]
+fn bytes32(s: &str) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&super::hash::from_hex(s).unwrap());
+    out
+}
+
+#[test]
+fn bip340_test_vectors() {
+    use super::hash::from_hex;
+    let secp = Secp256k1::new();
+    let csv = include_str!("../testdata/bip340_test_vectors.csv");
+    for line in csv.lines().skip(1) {
+        let fields: Vec<&str> = line.splitn(8, ',').collect();
+        let (index, secret_key, public_key, aux_rand, msg, signature, result) =
+            (fields[0], fields[1], fields[2], fields[3], fields[4], fields[5], fields[6]);
+        let public_key = bytes32(public_key);
+        let msg = from_hex(msg).unwrap();
+        let mut sig = [0u8; 64];
+        sig.copy_from_slice(&from_hex(signature).unwrap());
+        if !secret_key.is_empty() {
+            let sk = Scalar::from_be_bytes(&bytes32(secret_key)).unwrap();
+            assert_eq!(secp.x_only_public_key(&sk), public_key, "vector {}", index);
+            assert_eq!(&secp.schnorr_sign(&sk, &msg, &bytes32(aux_rand))[..], &sig[..], "vector {}", index);
+        }
+        assert_eq!(secp.schnorr_verify(&public_key, &msg, &sig), result == "TRUE", "vector {}", index);
+    }
+}
+
+#[test]
+fn taproot_tweak_test() {
+    let secp = Secp256k1::new();
+    // BIP-341 wallet test vectors, key path only
+    let internal = bytes32("d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d");
+    let (output, _) = secp.taproot_tweak_public_key(&internal, None).unwrap();
+    assert_eq!(output, bytes32("53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343"));
+    let sk = Scalar::from_be_bytes(&bytes32("6b973d88838f27366ed61c9ad6367663045cb456e28335c109e30717ae0c6baa")).unwrap();
+    assert_eq!(secp.x_only_public_key(&sk), internal);
+    let tweaked = secp.taproot_tweak_secret_key(&sk, None).unwrap();
+    assert_eq!(tweaked.to_be_bytes(), bytes32("2405b971772ad26915c8dcdf10f238753a9b837e5f8e6a86fd7c0cce5b7296d9"));
+    assert_eq!(secp.x_only_public_key(&tweaked), output);
+    let sig = secp.schnorr_sign(&tweaked, b"spend", &[0; 32]);
+    assert!(secp.schnorr_verify(&output, b"spend", &sig));
+    assert!(!secp.schnorr_verify(&internal, b"spend", &sig));
+
+    // one leaf script tree
+    let script = super::hash::from_hex("20d85a959b0290bf19bb89ed43c916be835475d013da4b362117393e25a48229b8ac").unwrap();
+    let leaf = tap_leaf_hash(0xc0, &script);
+    assert_eq!(leaf, bytes32("5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21"));
+    let internal = bytes32("187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27");
+    let (output, odd) = secp.taproot_tweak_public_key(&internal, Some(&leaf)).unwrap();
+    assert_eq!(output, bytes32("147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3"));
+    assert!(odd);
+    assert_eq!(tap_branch_hash(&leaf, &internal), tap_branch_hash(&internal, &leaf));
+}
@@ -10,6 +10,7 @@ use super::term_builder::TermBuildable;
 use super::division_polynomial;
 use crate::bigint;
 use crate::bigint::{Power};
+use crate::modular::BarrettContext;
 
 type TermBuilder = term_builder::TermBuilder;
 type Polynomial = polynomial::Polynomial;
@@ -18,6 +19,7 @@ type Polynomial = polynomial::Polynomial;
 /// schoof algorithm
 pub fn schoof(a: &BigInt, b: &BigInt, q: i32) -> Vec<bigint::ModResult> {
     let qq = BigInt::from(q);
+    let ctx = BarrettContext::new(&qq);
     let mut mod_result: Vec<bigint::ModResult> = Vec::new();
     // l = 2
     let l: BigInt = 2.into();
@@ -101,7 +103,7 @@ pub fn schoof(a: &BigInt, b: &BigInt, q: i32) -> Vec<bigint::ModResult> {
 
             let p1 = p1.reduction_modular(a, b, q);
 
-            let mut p1 = p1.polynomial_modular(&psi_l, &qq);
+            let mut p1 = p1.polynomial_modular_with(&psi_l, &ctx);
             p1.modular_assign(&qq);
             println!("{} pol % psi({}):{}", line!(), l, p1);
 
@@ -167,7 +169,7 @@ pub fn schoof(a: &BigInt, b: &BigInt, q: i32) -> Vec<bigint::ModResult> {
 
             p9.modular_assign(&qq);
 
-            let p9 = p9.polynomial_modular(&psi_l, &qq);
+            let p9 = p9.polynomial_modular_with(&psi_l, &ctx);
             println!("{} pol % psi({}):{}", line!(), l, p9);
 
             if !p9.is_zero() {
@@ -203,7 +205,7 @@ pub fn schoof(a: &BigInt, b: &BigInt, q: i32) -> Vec<bigint::ModResult> {
                 let p14 = &division_polynomial::psi(&qq, b, l) % q;
 
                 let qq = BigInt::from(q);
-                let p15 = p13.polynomial_modular(&p14, &qq);
+                let p15 = p13.polynomial_modular_with(&p14, &ctx);
 
                 if !p15.is_zero() {
                     mod_result.push(bigint::ModResult { l: BigInt::from(l), r: BigInt::from(0) });
@@ -68,6 +68,15 @@ fn sea_test1() {
     assert_eq!(result.isogeny_j_invariants.len(), 2);
     assert_eq_str!(result.isogeny_j_invariants[0], "20");
     assert_eq_str!(result.isogeny_j_invariants[1], "22");
+
+    // isogenous curves have the same number of points up to quadratic twist
+    for j in &result.isogeny_j_invariants {
+        let mut isogenous = elliptic_curve::EllipticCurve::from_j_invariant(j, &p);
+        isogenous.create_points();
+        assert_eq!(&isogenous.j_invariant(), j);
+        let n = isogenous.cardinality();
+        assert!(n == ec.cardinality() || n == 2 * 23 + 2 - ec.cardinality());
+    }
 }
 
 #[test]
@@ -0,0 +1,161 @@
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+use num_traits::One;
+use std::fmt;
+use super::elliptic_curve::{EllipticCurve, ECPoint};
+use super::hash::{from_hex, to_hex};
+
+/// reason an octet string is not a point, SEC 1 2.3.4
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointEncodingError {
+    InvalidHex,
+    InvalidLength,
+    /// first octet not 00, 02, 03, 04, 06 or 07
+    InvalidTag,
+    /// coordinate >= p
+    OutOfRange,
+    /// off-curve point or no square root for a compressed x
+    NotOnCurve,
+    /// the parity in a hybrid tag differs from y
+    WrongParity,
+}
+
+impl fmt::Display for PointEncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            PointEncodingError::InvalidHex => "invalid hex string",
+            PointEncodingError::InvalidLength => "invalid length",
+            PointEncodingError::InvalidTag => "invalid first octet",
+            PointEncodingError::OutOfRange => "coordinate out of range",
+            PointEncodingError::NotOnCurve => "point is not on curve",
+            PointEncodingError::WrongParity => "y parity does not match the hybrid tag",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl EllipticCurve {
+    /// ceil(bits(p) / 8), octets of a field element
+    pub fn field_length(&self) -> usize {
+        self.p.bits().div_ceil(8)
+    }
+
+    /// big-endian, left padded to field_length
+    fn field_to_bytes(&self, v: &BigInt) -> Vec<u8> {
+        let (_, bytes) = v.to_bytes_be();
+        let mut out = vec![0u8; self.field_length() - bytes.len()];
+        out.extend_from_slice(&bytes);
+        out
+    }
+
+    /// (x, y) with z = 1
+    fn to_affine(&self, point: &ECPoint) -> ECPoint {
+        let mut points = [point.clone()];
+        self.batch_normalize(&mut points);
+        let [point] = points;
+        point
+    }
+
+    /// 00 for infinity, 02 / 03 || x or 04 || x || y, SEC 1 2.3.3
+    pub fn to_sec1_bytes(&self, point: &ECPoint, compressed: bool) -> Vec<u8> {
+        if point.is_infinity() {
+            return vec![0];
+        }
+        let point = self.to_affine(point);
+        if compressed {
+            [&[2 + point.y.is_odd() as u8][..], &self.field_to_bytes(&point.x)].concat()
+        } else {
+            [&[4][..], &self.field_to_bytes(&point.x), &self.field_to_bytes(&point.y)].concat()
+        }
+    }
+
+    /// 06 / 07 || x || y, ANSI X9.62 hybrid form
+    pub fn to_sec1_hybrid_bytes(&self, point: &ECPoint) -> Vec<u8> {
+        let mut bytes = self.to_sec1_bytes(point, false);
+        if bytes[0] == 4 {
+            bytes[0] = 6 + self.to_affine(point).y.is_odd() as u8;
+        }
+        bytes
+    }
+
+    /// point of any of the encodings, SEC 1 2.3.4
+    pub fn from_sec1_bytes(&self, bytes: &[u8]) -> Result<ECPoint, PointEncodingError> {
+        let len = self.field_length();
+        let tag = *bytes.first().ok_or(PointEncodingError::InvalidLength)?;
+        let expected_length = match tag {
+            0 => 1,
+            2 | 3 => 1 + len,
+            4 | 6 | 7 => 1 + 2 * len,
+            _ => return Err(PointEncodingError::InvalidTag),
+        };
+        if bytes.len() != expected_length {
+            return Err(PointEncodingError::InvalidLength);
+        }
+        if tag == 0 {
+            return Ok(ECPoint::infinity());
+        }
+        let x = BigInt::from_bytes_be(Sign::Plus, &bytes[1..1 + len]);
+        if x >= self.p {
+            return Err(PointEncodingError::OutOfRange);
+        }
+        if tag == 2 || tag == 3 {
+            return self.lift_x(&x, tag == 3).ok_or(PointEncodingError::NotOnCurve);
+        }
+        let y = BigInt::from_bytes_be(Sign::Plus, &bytes[1 + len..]);
+        if y >= self.p {
+            return Err(PointEncodingError::OutOfRange);
+        }
+        let point = ECPoint::new(&x, &y, &One::one());
+        if !self.is_on_curve(&point) {
+            return Err(PointEncodingError::NotOnCurve);
+        }
+        if tag != 4 && y.is_odd() != (tag == 7) {
+            return Err(PointEncodingError::WrongParity);
+        }
+        Ok(point)
+    }
+
+    pub fn to_sec1_hex(&self, point: &ECPoint, compressed: bool) -> String {
+        to_hex(&self.to_sec1_bytes(point, compressed))
+    }
+
+    pub fn from_sec1_hex(&self, s: &str) -> Result<ECPoint, PointEncodingError> {
+        self.from_sec1_bytes(&from_hex(s).ok_or(PointEncodingError::InvalidHex)?)
+    }
+}
+
+#[test]
+fn sec1_test() {
+    use super::domain_parameters::DomainParameters;
+    let params = DomainParameters::secp256k1();
+    let ec = params.curve();
+    let g = &params.g;
+    let compressed = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+    let uncompressed = "0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\
+        483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+    assert_eq!(ec.to_sec1_hex(g, true), compressed);
+    assert_eq!(ec.to_sec1_hex(g, false), uncompressed);
+    assert_eq!(to_hex(&ec.to_sec1_hybrid_bytes(g)), format!("06{}", &uncompressed[2..]));
+    assert_eq!(ec.from_sec1_hex(compressed), Ok(g.clone()));
+    assert_eq!(ec.from_sec1_hex(uncompressed), Ok(g.clone()));
+    assert_eq!(ec.from_sec1_hex(&format!("06{}", &uncompressed[2..])), Ok(g.clone()));
+    assert_eq!(ec.from_sec1_hex("00"), Ok(ECPoint::infinity()));
+    assert_eq!(ec.to_sec1_bytes(&ECPoint::infinity(), true), vec![0]);
+
+    // Jacobian input
+    let q = ec.multiply_scalar(g, &BigInt::from(3));
+    let jacobian = ECPoint::new(&(&q.x * 4), &(&q.y * 8), &BigInt::from(2));
+    let bytes = ec.to_sec1_bytes(&jacobian, true);
+    assert_eq!(bytes[0], 2 + q.y.is_odd() as u8);
+    assert_eq!(ec.from_sec1_bytes(&bytes), Ok(q.clone()));
+    assert_eq!(ec.from_sec1_bytes(&ec.to_sec1_hybrid_bytes(&q)), Ok(q.clone()));
+
+    assert_eq!(ec.from_sec1_hex(&format!("07{}", &uncompressed[2..])), Err(PointEncodingError::WrongParity));
+    assert_eq!(ec.from_sec1_hex(&format!("{}9", &uncompressed[..129])), Err(PointEncodingError::NotOnCurve));
+    assert_eq!(ec.from_sec1_hex(&format!("02{}", "ff".repeat(32))), Err(PointEncodingError::OutOfRange));
+    // x = 5 is not the x coordinate of a point
+    assert_eq!(ec.from_sec1_hex(&format!("02{:064x}", 5)), Err(PointEncodingError::NotOnCurve));
+    assert_eq!(ec.from_sec1_hex(&compressed[..64]), Err(PointEncodingError::InvalidLength));
+    assert_eq!(ec.from_sec1_hex(&format!("05{}", &compressed[2..])), Err(PointEncodingError::InvalidTag));
+    assert_eq!(ec.from_sec1_hex("zz"), Err(PointEncodingError::InvalidHex));
+}
@@ -7,37 +7,190 @@
 //use num_traits::Zero;
 //use num_traits::ToPrimitive;
 
-use crate::bigint::Power;
 use num_bigint::BigInt;
 use num_traits::One;
+use std::ops::Deref;
 use super::elliptic_curve;
+use super::domain_parameters::DomainParameters;
+use super::ecdsa::{Ecdsa, Signature};
+use super::u256::FieldElement;
+use super::scalar::Scalar;
 
+/// secp256k1 domain parameters, with the curve built once
 #[derive(Debug, Clone)]
 pub struct Secp256k1 {
+    pub params: DomainParameters,
     pub ec: elliptic_curve::EllipticCurve,
-    pub g: elliptic_curve::ECPoint,
 }
 
 impl Secp256k1 {
     pub fn new() -> Self {
-        let p = BigInt::from(2).power(256) - BigInt::from(2).power(32) - BigInt::from(977);
-        let a = BigInt::from(0);
-        let b = BigInt::from(7);
-        let ec = elliptic_curve::EllipticCurve::new_raw(&a, &b, &p);
-        let gx = BigInt::from(5) * BigInt::from(10).power((12 * 3 + 2) * 2)
-                   + BigInt::from(50_662_630_222_773_436_695_787_188_951_685_343_262u128) * BigInt::from(10).power(12 * 3 + 2)
-                   + BigInt::from(50_603_453_777_594_175_500_187_360_389_116_729_240u128);
-        let gy = BigInt::from(3) * BigInt::from(10).power((12 * 3 + 2) * 2)
-                   + BigInt::from(26_705_100_207_588_169_780_830_851_305_070_431_844u128) * BigInt::from(10).power(12 * 3 + 2)
-                   + BigInt::from(71_273_380_659_243_275_938_904_335_757_337_482_424u128);
-        let g = elliptic_curve::ECPoint::new(
-            &gx,
-            &gy,
-            &One::one());
-
-        Secp256k1 {
-            ec: ec,
-            g: g, 
+        let params = DomainParameters::secp256k1();
+        let ec = params.curve();
+        Secp256k1 { params, ec }
+    }
+}
+
+impl Deref for Secp256k1 {
+    type Target = DomainParameters;
+    fn deref(&self) -> &DomainParameters {
+        &self.params
+    }
+}
+
+impl Secp256k1 {
+    /// G on the fixed-width backend
+    pub fn generator(&self) -> Secp256k1Point {
+        Secp256k1Point::from(&self.g)
+    }
+
+    /// k P on the fixed-width backend
+    pub fn multiply_scalar(&self, point: &elliptic_curve::ECPoint, k: &Scalar) -> elliptic_curve::ECPoint {
+        Secp256k1Point::from(point).multiply(k).into()
+    }
+
+    /// public key of the signer of hash, Ethereum ecrecover with v = 27 + recovery_id
+    pub fn ecrecover(&self, hash: &[u8], signature: &Signature, recovery_id: u8) -> Option<elliptic_curve::ECPoint> {
+        Ecdsa::new(&self.params).recover_public_key(hash, signature, recovery_id)
+    }
+
+    /// n P, n reduced modulo the group order
+    pub fn multiply(&self, point: &elliptic_curve::ECPoint, n: &BigInt) -> elliptic_curve::ECPoint {
+        self.multiply_scalar(point, &Scalar::from_bigint(n))
+    }
+}
+
+/// Jacobian point (X : Y : Z) = (X/Z^2, Y/Z^3) on y^2 = x^3 + 7 over FieldElement
+/// Z = 0 is the point at infinity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Secp256k1Point {
+    pub x: FieldElement,
+    pub y: FieldElement,
+    pub z: FieldElement,
+}
+
+impl Secp256k1Point {
+    pub fn infinity() -> Secp256k1Point {
+        Secp256k1Point {
+            x: FieldElement::ONE,
+            y: FieldElement::ONE,
+            z: FieldElement::ZERO,
+        }
+    }
+
+    /// G of the secp256k1 domain parameters, without building the curve
+    pub fn generator() -> Secp256k1Point {
+        Secp256k1Point::from(&DomainParameters::secp256k1().g)
+    }
+
+    pub fn from_affine(x: &FieldElement, y: &FieldElement) -> Secp256k1Point {
+        Secp256k1Point { x: *x, y: *y, z: FieldElement::ONE }
+    }
+
+    /// point with x coordinate x and even y, None if x^3 + 7 is not a square
+    pub fn lift_x(x: &FieldElement) -> Option<Secp256k1Point> {
+        let y = x.square().mul(x).add(&FieldElement::from_u64(7)).sqrt()?;
+        let y = if y.is_odd() { y.neg() } else { y };
+        Some(Secp256k1Point::from_affine(x, &y))
+    }
+
+    pub fn is_infinity(&self) -> bool {
+        self.z.is_zero()
+    }
+
+    /// (x, y), None for infinity
+    pub fn to_affine(&self) -> Option<(FieldElement, FieldElement)> {
+        if self.is_infinity() {
+            return None;
+        }
+        let zi = self.z.inverse();
+        let zi2 = zi.square();
+        Some((self.x.mul(&zi2), self.y.mul(&zi2.mul(&zi))))
+    }
+
+    pub fn negate(&self) -> Secp256k1Point {
+        Secp256k1Point { x: self.x, y: self.y.neg(), z: self.z }
+    }
+
+    /// dbl-2009-l, a = 0
+    pub fn double(&self) -> Secp256k1Point {
+        if self.is_infinity() || self.y.is_zero() {
+            return Secp256k1Point::infinity();
+        }
+        let a = self.x.square();
+        let b = self.y.square();
+        let c = b.square();
+        let d = self.x.add(&b).square().sub(&a).sub(&c);
+        let d = d.add(&d);
+        let e = a.mul_u64(3);
+        let f = e.square();
+        let x3 = f.sub(&d.add(&d));
+        let y3 = e.mul(&d.sub(&x3)).sub(&c.mul_u64(8));
+        let yz = self.y.mul(&self.z);
+        Secp256k1Point { x: x3, y: y3, z: yz.add(&yz) }
+    }
+
+    /// U1 = X1 Z2^2, U2 = X2 Z1^2, S1 = Y1 Z2^3, S2 = Y2 Z1^3, H = U2 - U1, R = S2 - S1
+    /// X3 = R^2 - H^3 - 2 U1 H^2, Y3 = R (U1 H^2 - X3) - S1 H^3, Z3 = H Z1 Z2
+    pub fn add(&self, other: &Secp256k1Point) -> Secp256k1Point {
+        if self.is_infinity() {
+            return *other;
+        }
+        if other.is_infinity() {
+            return *self;
+        }
+        let z1z1 = self.z.square();
+        let z2z2 = other.z.square();
+        let u1 = self.x.mul(&z2z2);
+        let u2 = other.x.mul(&z1z1);
+        let s1 = self.y.mul(&z2z2).mul(&other.z);
+        let s2 = other.y.mul(&z1z1).mul(&self.z);
+        let h = u2.sub(&u1);
+        let r = s2.sub(&s1);
+        if h.is_zero() {
+            return if r.is_zero() { self.double() } else { Secp256k1Point::infinity() };
+        }
+        let h2 = h.square();
+        let h3 = h2.mul(&h);
+        let u1h2 = u1.mul(&h2);
+        let x3 = r.square().sub(&h3).sub(&u1h2.add(&u1h2));
+        let y3 = r.mul(&u1h2.sub(&x3)).sub(&s1.mul(&h3));
+        let z3 = h.mul(&self.z).mul(&other.z);
+        Secp256k1Point { x: x3, y: y3, z: z3 }
+    }
+
+    /// double and add
+    pub fn multiply(&self, k: &Scalar) -> Secp256k1Point {
+        let n = k.to_u256();
+        let mut r = Secp256k1Point::infinity();
+        for i in (0..n.bits()).rev() {
+            r = r.double();
+            if n.bit(i) {
+                r = r.add(self);
+            }
+        }
+        r
+    }
+}
+
+impl From<&elliptic_curve::ECPoint> for Secp256k1Point {
+    fn from(point: &elliptic_curve::ECPoint) -> Secp256k1Point {
+        if point.is_infinity() {
+            return Secp256k1Point::infinity();
+        }
+        Secp256k1Point {
+            x: FieldElement::from_bigint(&point.x),
+            y: FieldElement::from_bigint(&point.y),
+            z: FieldElement::from_bigint(&point.z),
+        }
+    }
+}
+
+impl From<Secp256k1Point> for elliptic_curve::ECPoint {
+    fn from(point: Secp256k1Point) -> elliptic_curve::ECPoint {
+        match point.to_affine() {
+            None => elliptic_curve::ECPoint::infinity(),
+            Some((x, y)) => elliptic_curve::ECPoint::new(&x.to_bigint(), &y.to_bigint(), &One::one()),
         }
     }
 }
@@ -45,8 +198,8 @@ impl Secp256k1 {
 #[test]
 fn secp256k1_test1() {
     let curve = Secp256k1::new();
-    let ec = curve.ec;
-    let g = curve.g;
+    let ec = curve.ec.clone();
+    let g = curve.g.clone();
 
     assert!(ec.is_on_curve(&g));
 
@@ -58,17 +211,75 @@ fn secp256k1_test1() {
         "(112711660439710606056748659173929673102114977341539408544630613555209775888121, 25583027980570883691656905877401976406448868254816295069919888960541586679410)");
 }
 
+#[test]
+fn secp256k1_backend_test() {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+    let curve = Secp256k1::new();
+    let ec = &curve.ec;
+    let g = &curve.g;
+
+    assert_eq_str!(curve.multiply(g, &1.into()),
+        "(55066263022277343669578718895168534326250603453777594175500187360389116729240, 32670510020758816978083085130507043184471273380659243275938904335757337482424)");
+    assert_eq_str!(curve.multiply(g, &2.into()),
+        "(89565891926547004231252920425935692360644145829622209833684329913297188986597, 12158399299693830322967808612713398636155367887041628176798871954788371653930)");
+    assert_eq_str!(curve.multiply(g, &3.into()),
+        "(112711660439710606056748659173929673102114977341539408544630613555209775888121, 25583027980570883691656905877401976406448868254816295069919888960541586679410)");
+    assert!(curve.multiply(g, &0.into()).is_infinity());
+    assert_eq!(Secp256k1Point::generator(), curve.generator());
+    assert_eq!(curve.multiply(g, &(-3).into()), ec.negate(&ec.multiply_scalar(g, &3.into())));
+
+    let mut rng = StdRng::seed_from_u64(38);
+    for _ in 0..3 {
+        let k = BigInt::from(rng.gen::<u128>()) + (BigInt::from(rng.gen::<u128>()) << 128);
+        let q = curve.multiply(g, &k);
+        assert!(ec.is_on_curve(&q));
+        assert_eq!(q, ec.multiply_scalar(g, &k));
+    }
+
+    let k = Scalar::random(&mut rng);
+    assert_eq!(curve.multiply_scalar(g, &k), ec.multiply_scalar(g, &k.to_bigint()));
+    assert_eq!(curve.multiply_scalar(g, &k.neg()), ec.negate(&curve.multiply_scalar(g, &k)));
+    assert!(curve.multiply(g, &curve.n).is_infinity());
+    assert_eq!(curve.n, Scalar::order());
+    assert_eq!(curve.validate(), Ok(()));
+    assert_eq!(curve.multiply(g, &(&curve.n + 2)), curve.multiply(g, &2.into()));
+    assert_eq_str!(curve.h, "1");
+
+    let p = Secp256k1Point::from(g);
+    assert!(p.add(&p.negate()).is_infinity());
+    assert_eq!(p.add(&p).to_affine(), p.double().to_affine());
+}
+
+#[test]
+fn secp256k1_ecrecover_test() {
+    use super::hash::sha256;
+    let curve = Secp256k1::new();
+    let ecdsa = Ecdsa::new(&curve.params);
+    let d = BigInt::from(0x1234_5678u64);
+    let q = ecdsa.public_key(&d);
+    let hash = sha256(b"recover me");
+    let (signature, recovery_id) = ecdsa.sign_hash_recoverable(&d, &hash);
+    assert_eq!(curve.ecrecover(&hash, &signature, recovery_id), Some(q.clone()));
+    assert_ne!(curve.ecrecover(&hash, &signature, recovery_id ^ 1), Some(q.clone()));
+    assert_ne!(curve.ecrecover(&sha256(b"other"), &signature, recovery_id), Some(q));
+    assert_eq!(curve.ecrecover(&hash, &signature, 4), None);
+    // r + n exceeds p for all but a tiny range of r
+    assert_eq!(curve.ecrecover(&hash, &signature, recovery_id | 2), None);
+    assert_eq!(curve.ecrecover(&hash, &Signature::new(&signature.r, &curve.n), recovery_id), None);
+}
+
 #[test]
 #[ignore]
 fn secp256k1_test2() {
     use rand::Rng;
     use std::io::{self, Write};
+    use crate::bigint::Power;
 
     let mut rng = rand::thread_rng();
 
     let curve = Secp256k1::new();
-    let ec = curve.ec;
-    let g = curve.g;
+    let ec = curve.ec.clone();
+    let g = curve.g.clone();
 
     let sx = BigInt::from(6) * BigInt::from(10).power(38 * 2)
                         + BigInt::from(81671193674923859464776141273914116991u128) * BigInt::from(10).power(38)
@@ -0,0 +1,226 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Zero, One};
+use crate::bigint::{Inverse, Power, RootModulo};
+use super::elliptic_curve::{EllipticCurve, ECPoint};
+
+/// singular point of y^2 = x^3 + a x + b
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SingularPointType {
+    /// y^2 = x^3, nonsingular points are isomorphic to F_p^+
+    Cusp,
+    /// tangents defined over F_p, nonsingular points are isomorphic to F_p^*
+    SplitNode,
+    /// tangents defined over F_p^2, nonsingular points are isomorphic to
+    /// the norm one torus of F_p^2
+    NonSplitNode,
+}
+
+/// image of a nonsingular point
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupElement {
+    /// t in F_p^+
+    Additive(BigInt),
+    /// s in F_p^*
+    Multiplicative(BigInt),
+    /// c0 + c1 alpha, c0^2 - alpha^2 c1^2 = 1
+    Torus(BigInt, BigInt),
+}
+
+/// y^2 = x^3 + a x + b with 4 a^3 + 27 b^2 = 0
+/// GF(p), p > 3
+///
+/// y^2 = (x - x0)^2 (x + 2 x0), singular point (x0, 0)
+#[derive(Debug, Clone)]
+pub struct SingularCubic {
+    pub ec: EllipticCurve,
+    pub x0: BigInt,
+    /// slopes of the tangents at the singular point are +-alpha, alpha^2 = 3 x0
+    pub alpha2: BigInt,
+    alpha: Option<BigInt>,
+    pub point_type: SingularPointType,
+}
+
+impl SingularCubic {
+    pub fn new(a: &BigInt, b: &BigInt, p: &BigInt) -> SingularCubic {
+        assert!(p > &BigInt::from(3));
+        let ec = EllipticCurve::new_raw(&a.mod_floor(p), &b.mod_floor(p), p);
+        assert!(ec.is_singular(), "curve is not singular");
+        if ec.a.is_zero() {
+            return SingularCubic {
+                ec,
+                x0: Zero::zero(),
+                alpha2: Zero::zero(),
+                alpha: None,
+                point_type: SingularPointType::Cusp,
+            };
+        }
+        // x0 = -3 b / 2 a
+        let x0 = (BigInt::from(-3) * &ec.b * (BigInt::from(2) * &ec.a).inverse(p)).mod_floor(p);
+        let alpha2 = (BigInt::from(3) * &x0).mod_floor(p);
+        let alpha = alpha2.sqrt_modulo(p);
+        let point_type = if alpha.is_some() {
+            SingularPointType::SplitNode
+        } else {
+            SingularPointType::NonSplitNode
+        };
+        SingularCubic { ec, x0, alpha2, alpha, point_type }
+    }
+
+    pub fn singular_point(&self) -> ECPoint {
+        ECPoint::new(&self.x0, &Zero::zero(), &One::one())
+    }
+
+    /// order of the group of nonsingular points
+    pub fn group_order(&self) -> BigInt {
+        match self.point_type {
+            SingularPointType::Cusp => self.ec.p.clone(),
+            SingularPointType::SplitNode => &self.ec.p - 1,
+            SingularPointType::NonSplitNode => &self.ec.p + 1,
+        }
+    }
+
+    /// rational points except the singular point
+    pub fn nonsingular_points(&self) -> Vec<ECPoint> {
+        let mut ec = self.ec.clone();
+        ec.create_points();
+        let singular_point = self.singular_point();
+        ec.points().into_iter().filter(|point| point != &singular_point).collect()
+    }
+
+    /// isomorphism from the nonsingular points to the group
+    pub fn to_group(&self, point: &ECPoint) -> GroupElement {
+        assert!(self.ec.is_on_curve(point), "point is not on curve");
+        let p = &self.ec.p;
+        if point.is_infinity() {
+            return self.identity();
+        }
+        let x = (&point.x - &self.x0).mod_floor(p);
+        let y = point.y.mod_floor(p);
+        assert!(!(x.is_zero() && y.is_zero()), "singular point");
+        match self.point_type {
+            SingularPointType::Cusp => {
+                // t = x / y
+                GroupElement::Additive((x * y.inverse(p)).mod_floor(p))
+            }
+            SingularPointType::SplitNode => {
+                // s = (y + alpha x) / (y - alpha x)
+                let alpha = self.alpha.as_ref().unwrap();
+                let s = (&y + alpha * &x) * (&y - alpha * &x).inverse(p);
+                GroupElement::Multiplicative(s.mod_floor(p))
+            }
+            SingularPointType::NonSplitNode => {
+                // (y + alpha x)^2 / (y^2 - alpha^2 x^2), y^2 - alpha^2 x^2 = x^3
+                let d = x.power(3).inverse(p);
+                let c0 = (y.power(2) + &self.alpha2 * x.power(2)) * &d;
+                let c1 = BigInt::from(2) * &y * &x * &d;
+                GroupElement::Torus(c0.mod_floor(p), c1.mod_floor(p))
+            }
+        }
+    }
+
+    /// inverse of to_group
+    pub fn from_group(&self, element: &GroupElement) -> ECPoint {
+        let p = &self.ec.p;
+        if element == &self.identity() {
+            return ECPoint::infinity();
+        }
+        let (x, y) = match (self.point_type, element) {
+            (SingularPointType::Cusp, GroupElement::Additive(t)) => {
+                // (1 / t^2, 1 / t^3)
+                let ti = t.inverse(p);
+                (ti.power(2), ti.power(3))
+            }
+            (SingularPointType::SplitNode, GroupElement::Multiplicative(s)) => {
+                // x = 4 alpha^2 s / (s - 1)^2, y = alpha x (s + 1) / (s - 1)
+                let alpha = self.alpha.as_ref().unwrap();
+                let si = (s - BigInt::from(1)).inverse(p);
+                let x = BigInt::from(4) * &self.alpha2 * s * si.power(2);
+                let y = alpha * &x * (s + 1) * si;
+                (x, y)
+            }
+            (SingularPointType::NonSplitNode, GroupElement::Torus(c0, c1)) => {
+                // x = 2 alpha^2 / (c0 - 1), y = alpha^2 x c1 / (c0 - 1)
+                let ci = (c0 - BigInt::from(1)).inverse(p);
+                let x = BigInt::from(2) * &self.alpha2 * &ci;
+                let y = &self.alpha2 * &x * c1 * ci;
+                (x, y)
+            }
+            _ => panic!("group element does not match {:?}", self.point_type),
+        };
+        ECPoint::new(&(x + &self.x0).mod_floor(p), &y.mod_floor(p), &One::one())
+    }
+
+    pub fn identity(&self) -> GroupElement {
+        match self.point_type {
+            SingularPointType::Cusp => GroupElement::Additive(Zero::zero()),
+            SingularPointType::SplitNode => GroupElement::Multiplicative(One::one()),
+            SingularPointType::NonSplitNode => GroupElement::Torus(One::one(), Zero::zero()),
+        }
+    }
+
+    /// group law of F_p^+, F_p^* or the norm one torus
+    pub fn operate(&self, e1: &GroupElement, e2: &GroupElement) -> GroupElement {
+        let p = &self.ec.p;
+        match (e1, e2) {
+            (GroupElement::Additive(t1), GroupElement::Additive(t2)) => {
+                GroupElement::Additive((t1 + t2).mod_floor(p))
+            }
+            (GroupElement::Multiplicative(s1), GroupElement::Multiplicative(s2)) => {
+                GroupElement::Multiplicative((s1 * s2).mod_floor(p))
+            }
+            (GroupElement::Torus(a0, a1), GroupElement::Torus(b0, b1)) => {
+                let c0 = a0 * b0 + &self.alpha2 * a1 * b1;
+                let c1 = a0 * b1 + a1 * b0;
+                GroupElement::Torus(c0.mod_floor(p), c1.mod_floor(p))
+            }
+            _ => panic!("group elements do not match"),
+        }
+    }
+}
+
+#[cfg(test)]
+fn check_isomorphism(cubic: &SingularCubic) {
+    let points = cubic.nonsingular_points();
+    assert_eq!(BigInt::from(points.len()), cubic.group_order());
+    for point1 in &points {
+        let e1 = cubic.to_group(point1);
+        assert_eq!(&cubic.from_group(&e1), point1);
+        for point2 in &points {
+            let e2 = cubic.to_group(point2);
+            let sum = cubic.ec.plus(point1, point2);
+            assert_eq!(cubic.to_group(&sum), cubic.operate(&e1, &e2));
+        }
+    }
+}
+
+#[test]
+fn singular_cubic_cusp_test() {
+    let cubic = SingularCubic::new(&BigInt::from(0), &BigInt::from(0), &BigInt::from(7));
+    assert_eq!(cubic.point_type, SingularPointType::Cusp);
+    assert_eq_str!(cubic.singular_point(), "(0, 0)");
+    assert_eq_str!(cubic.group_order(), "7");
+    check_isomorphism(&cubic);
+    let point = ECPoint::new(&BigInt::from(4), &BigInt::from(1), &BigInt::from(1));
+    assert_eq!(cubic.to_group(&point), GroupElement::Additive(BigInt::from(4)));
+}
+
+#[test]
+fn singular_cubic_split_node_test() {
+    // y^2 = (x - 1)^2 (x + 2), 3 is a square mod 11
+    let cubic = SingularCubic::new(&BigInt::from(-3), &BigInt::from(2), &BigInt::from(11));
+    assert_eq!(cubic.point_type, SingularPointType::SplitNode);
+    assert_eq_str!(cubic.singular_point(), "(1, 0)");
+    assert_eq_str!(cubic.group_order(), "10");
+    check_isomorphism(&cubic);
+}
+
+#[test]
+fn singular_cubic_non_split_node_test() {
+    // y^2 = (x - 1)^2 (x + 2), 3 is not a square mod 7
+    let cubic = SingularCubic::new(&BigInt::from(-3), &BigInt::from(2), &BigInt::from(7));
+    assert_eq!(cubic.point_type, SingularPointType::NonSplitNode);
+    assert_eq_str!(cubic.singular_point(), "(1, 0)");
+    assert_eq_str!(cubic.group_order(), "8");
+    check_isomorphism(&cubic);
+}
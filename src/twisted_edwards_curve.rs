@@ -0,0 +1,318 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Zero, One};
+use crate::bigint::{Inverse, Power, RootModulo};
+use super::curve::Curve;
+use super::elliptic_curve::{EllipticCurve, ECPoint};
+use super::montgomery_curve::MontgomeryCurve;
+
+/// a x^2 + y^2 = 1 + d x^2 y^2
+/// GF(p)
+#[derive(Debug, Clone)]
+pub struct TwistedEdwardsCurve {
+    pub a: BigInt,
+    pub d: BigInt,
+    pub p: BigInt,
+}
+
+/// Extended coordinates point
+/// x = X / Z, y = Y / Z, T = X Y / Z
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EdwardsPoint {
+    pub x: BigInt,
+    pub y: BigInt,
+    pub z: BigInt,
+    pub t: BigInt,
+}
+
+impl EdwardsPoint {
+    /// affine point (x, y)
+    pub fn new(x: &BigInt, y: &BigInt) -> EdwardsPoint {
+        EdwardsPoint {
+            x: x.clone(),
+            y: y.clone(),
+            z: One::one(),
+            t: x * y,
+        }
+    }
+
+    /// (0, 1)
+    pub fn identity() -> EdwardsPoint {
+        EdwardsPoint::new(&Zero::zero(), &One::one())
+    }
+}
+
+impl TwistedEdwardsCurve {
+    pub fn new(a: &BigInt, d: &BigInt, p: &BigInt) -> TwistedEdwardsCurve {
+        let curve = TwistedEdwardsCurve {
+            a: a.mod_floor(p),
+            d: d.mod_floor(p),
+            p: p.clone(),
+        };
+        assert!(!(&curve.a * &curve.d * (&curve.a - &curve.d)).mod_floor(p).is_zero(), "singular curve");
+        curve
+    }
+
+    /// -x^2 + y^2 = 1 - 121665/121666 x^2 y^2 over GF(2^255 - 19)
+    pub fn ed25519() -> TwistedEdwardsCurve {
+        let p = BigInt::from(2).power(255) - BigInt::from(19);
+        let d = BigInt::from(-121_665) * BigInt::from(121_666).inverse(&p);
+        TwistedEdwardsCurve::new(&BigInt::from(-1), &d, &p)
+    }
+
+    /// a is a square and d is not a square
+    /// then the addition law has no exceptional cases
+    pub fn is_complete(&self) -> bool {
+        self.a.sqrt_modulo(&self.p).is_some() && self.d.sqrt_modulo(&self.p).is_none()
+    }
+
+    pub fn is_on_curve(&self, point: &EdwardsPoint) -> bool {
+        let p = &self.p;
+        let x2 = point.x.power(2);
+        let y2 = point.y.power(2);
+        let z2 = point.z.power(2);
+        // a X^2 Z^2 + Y^2 Z^2 = Z^4 + d X^2 Y^2
+        let lhs = (&self.a * &x2 + &y2) * &z2;
+        let rhs = z2.power(2) + &self.d * &x2 * &y2;
+        !point.z.mod_floor(p).is_zero()
+            && (lhs - rhs).mod_floor(p).is_zero()
+            && (&point.x * &point.y - &point.z * &point.t).mod_floor(p).is_zero()
+    }
+
+    /// (x, y, 1, x y)
+    pub fn normalize(&self, point: &EdwardsPoint) -> EdwardsPoint {
+        let p = &self.p;
+        let zi = point.z.inverse(p);
+        let x = (&point.x * &zi).mod_floor(p);
+        let y = (&point.y * &zi).mod_floor(p);
+        EdwardsPoint {
+            t: (&x * &y).mod_floor(p),
+            x,
+            y,
+            z: One::one(),
+        }
+    }
+
+    /// projective equality
+    pub fn is_equal(&self, point1: &EdwardsPoint, point2: &EdwardsPoint) -> bool {
+        let p = &self.p;
+        (&point1.x * &point2.z - &point2.x * &point1.z).mod_floor(p).is_zero()
+            && (&point1.y * &point2.z - &point2.y * &point1.z).mod_floor(p).is_zero()
+    }
+
+    pub fn is_identity(&self, point: &EdwardsPoint) -> bool {
+        self.is_equal(point, &EdwardsPoint::identity())
+    }
+
+    /// Point negation: -(x, y) = (-x, y)
+    pub fn negate(&self, point: &EdwardsPoint) -> EdwardsPoint {
+        EdwardsPoint {
+            x: (-&point.x).mod_floor(&self.p),
+            y: point.y.clone(),
+            z: point.z.clone(),
+            t: (-&point.t).mod_floor(&self.p),
+        }
+    }
+
+    /// Unified point addition in extended coordinates (add-2008-hwcd)
+    /// also valid for doubling, complete if is_complete()
+    pub fn plus(&self, point1: &EdwardsPoint, point2: &EdwardsPoint) -> EdwardsPoint {
+        let p = &self.p;
+        let a = &point1.x * &point2.x;
+        let b = &point1.y * &point2.y;
+        let c = &self.d * &point1.t * &point2.t;
+        let d = &point1.z * &point2.z;
+        let e = (&point1.x + &point1.y) * (&point2.x + &point2.y) - &a - &b;
+        let f = &d - &c;
+        let g = &d + &c;
+        let h = &b - &self.a * &a;
+        EdwardsPoint {
+            x: (&e * &f).mod_floor(p),
+            y: (&g * &h).mod_floor(p),
+            z: (&f * &g).mod_floor(p),
+            t: (&e * &h).mod_floor(p),
+        }
+    }
+
+    /// rational points
+    pub fn points(&self) -> Vec<EdwardsPoint> {
+        let mut points: Vec<EdwardsPoint> = Vec::new();
+        for x in num_iter::range(BigInt::from(0), self.p.clone()) {
+            for y in num_iter::range(BigInt::from(0), self.p.clone()) {
+                let point = EdwardsPoint::new(&x, &y);
+                if self.is_on_curve(&point) {
+                    points.push(point);
+                }
+            }
+        }
+        points
+    }
+
+    /// B v^2 = u^3 + A u^2 + u
+    /// A = 2 (a + d) / (a - d), B = 4 / (a - d)
+    pub fn to_montgomery(&self) -> MontgomeryCurve {
+        let p = &self.p;
+        let i = (&self.a - &self.d).inverse(p);
+        let a = BigInt::from(2) * (&self.a + &self.d) * &i;
+        let b = BigInt::from(4) * &i;
+        MontgomeryCurve::new(&a, &b, p)
+    }
+
+    /// (x, y) -> (u, v) = ((1 + y) / (1 - y), u / x)
+    pub fn to_montgomery_point(&self, point: &EdwardsPoint) -> ECPoint {
+        let p = &self.p;
+        let point = self.normalize(point);
+        if point.x.is_zero() {
+            if point.y.is_one() {
+                return ECPoint::infinity();
+            }
+            // (0, -1) is the point of order 2
+            return ECPoint::new(&Zero::zero(), &Zero::zero(), &One::one());
+        }
+        let u = ((BigInt::from(1) + &point.y) * (BigInt::from(1) - &point.y).inverse(p)).mod_floor(p);
+        let v = (&u * point.x.inverse(p)).mod_floor(p);
+        ECPoint::new(&u, &v, &One::one())
+    }
+
+    /// (u, v) -> (x, y) = (u / v, (u - 1) / (u + 1))
+    pub fn from_montgomery_point(&self, point: &ECPoint) -> EdwardsPoint {
+        let p = &self.p;
+        if point.is_infinity() {
+            return EdwardsPoint::identity();
+        }
+        let u = point.x.mod_floor(p);
+        let v = point.y.mod_floor(p);
+        if u.is_zero() && v.is_zero() {
+            return EdwardsPoint::new(&Zero::zero(), &(p - 1));
+        }
+        assert!(!v.is_zero() && !(&u + BigInt::from(1)).mod_floor(p).is_zero(), "exceptional point");
+        let x = (&u * v.inverse(p)).mod_floor(p);
+        let y = ((&u - BigInt::from(1)) * (&u + BigInt::from(1)).inverse(p)).mod_floor(p);
+        EdwardsPoint::new(&x, &y)
+    }
+
+    /// short Weierstrass form via the Montgomery form
+    pub fn to_weierstrass(&self) -> EllipticCurve {
+        self.to_montgomery().to_weierstrass()
+    }
+
+    pub fn to_weierstrass_point(&self, point: &EdwardsPoint) -> ECPoint {
+        self.to_montgomery().to_weierstrass_point(&self.to_montgomery_point(point))
+    }
+
+    pub fn from_weierstrass_point(&self, point: &ECPoint) -> EdwardsPoint {
+        self.from_montgomery_point(&self.to_montgomery().from_weierstrass_point(point))
+    }
+}
+
+impl Curve for TwistedEdwardsCurve {
+    type Point = EdwardsPoint;
+
+    fn identity(&self) -> EdwardsPoint {
+        EdwardsPoint::identity()
+    }
+
+    fn is_on_curve(&self, point: &EdwardsPoint) -> bool {
+        TwistedEdwardsCurve::is_on_curve(self, point)
+    }
+
+    fn negate(&self, point: &EdwardsPoint) -> EdwardsPoint {
+        TwistedEdwardsCurve::negate(self, point)
+    }
+
+    fn plus(&self, point1: &EdwardsPoint, point2: &EdwardsPoint) -> EdwardsPoint {
+        TwistedEdwardsCurve::plus(self, point1, point2)
+    }
+
+    fn normalize(&self, point: &EdwardsPoint) -> EdwardsPoint {
+        TwistedEdwardsCurve::normalize(self, point)
+    }
+}
+
+/// Ed25519 curve and base point
+#[derive(Debug, Clone)]
+pub struct Ed25519 {
+    pub curve: TwistedEdwardsCurve,
+    pub g: EdwardsPoint,
+    /// order of g
+    pub l: BigInt,
+}
+
+impl Ed25519 {
+    pub fn new() -> Self {
+        let curve = TwistedEdwardsCurve::ed25519();
+        let gx = BigInt::parse_bytes(
+            b"15112221349535400772501151409588531511454012693041857206046113283949847762202", 10).unwrap();
+        // 4 / 5
+        let gy = BigInt::parse_bytes(
+            b"46316835694926478169428394003475163141307993866256225615783033603165251855960", 10).unwrap();
+        let l = BigInt::from(2).power(252)
+            + BigInt::parse_bytes(b"27742317777372353535851937790883648493", 10).unwrap();
+        Ed25519 {
+            g: EdwardsPoint::new(&gx, &gy),
+            curve,
+            l,
+        }
+    }
+}
+
+impl Default for Ed25519 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn twisted_edwards_curve_test() {
+    // x^2 + y^2 = 1 + 2 x^2 y^2, 2 is not a square mod 13
+    let p = BigInt::from(13);
+    let curve = TwistedEdwardsCurve::new(&BigInt::from(1), &BigInt::from(2), &p);
+    assert!(curve.is_complete());
+    let points = curve.points();
+    let ec = curve.to_weierstrass();
+    assert_eq!(points.len(), EllipticCurve::new(&ec.a, &ec.b, &p).cardinality());
+    assert_eq!(curve.to_montgomery().j_invariant(), ec.j_invariant());
+    let n = BigInt::from(points.len());
+    for point1 in &points {
+        assert!(curve.is_identity(&curve.multiply_scalar(point1, &n)));
+        let image1 = curve.to_weierstrass_point(point1);
+        assert!(ec.is_on_curve(&image1));
+        assert!(curve.is_equal(&curve.from_weierstrass_point(&image1), point1));
+        // unified addition covers doubling, inverses and the identity
+        for point2 in &points {
+            let sum = curve.plus(point1, point2);
+            assert!(curve.is_on_curve(&sum));
+            let image2 = curve.to_weierstrass_point(point2);
+            assert_eq!(curve.to_weierstrass_point(&sum), ec.plus(&image1, &image2));
+        }
+    }
+}
+
+#[test]
+fn twisted_edwards_incomplete_test() {
+    // d = 3 is a square mod 13
+    let curve = TwistedEdwardsCurve::new(&BigInt::from(1), &BigInt::from(3), &BigInt::from(13));
+    assert!(!curve.is_complete());
+}
+
+#[test]
+fn ed25519_test() {
+    let ed25519 = Ed25519::new();
+    let curve = &ed25519.curve;
+    let g = &ed25519.g;
+    assert!(curve.is_complete());
+    assert!(curve.is_on_curve(g));
+    assert_eq!((&g.y * BigInt::from(5)).mod_floor(&curve.p), BigInt::from(4));
+    assert!(curve.is_identity(&curve.multiply_scalar(g, &ed25519.l)));
+    assert!(!curve.is_identity(&curve.multiply_scalar(g, &(&ed25519.l - 1))));
+
+    // birationally equivalent to Curve25519 with base point u = 9
+    let montgomery = curve.to_montgomery();
+    let curve25519 = MontgomeryCurve::curve25519();
+    assert_eq!(montgomery.a, curve25519.a);
+    assert_eq_str!(curve.to_montgomery_point(g).x, "9");
+    let k = BigInt::from(1_234_567_890_123u64);
+    let kg = curve.to_montgomery_point(&curve.multiply_scalar(g, &k));
+    assert_eq!(kg.x, curve25519.ladder_x(&BigInt::from(9), &k));
+    assert_eq!(montgomery.j_invariant(), curve25519.j_invariant());
+}
@@ -0,0 +1,352 @@
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+use num_traits::Zero;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// 256-bit unsigned integer, 4 x 64-bit limbs, least significant first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct U256(pub [u64; 4]);
+
+impl U256 {
+    pub const ZERO: U256 = U256([0, 0, 0, 0]);
+    pub const ONE: U256 = U256([1, 0, 0, 0]);
+
+    /// 0 <= n < 2^256
+    pub fn from_bigint(n: &BigInt) -> U256 {
+        assert!(n >= &Zero::zero() && n.bits() <= 256, "out of range");
+        let (_, bytes) = n.to_bytes_le();
+        let mut limbs = [0u64; 4];
+        for (i, b) in bytes.iter().enumerate() {
+            limbs[i / 8] |= u64::from(*b) << (8 * (i % 8));
+        }
+        U256(limbs)
+    }
+
+    pub fn to_bigint(&self) -> BigInt {
+        BigInt::from_bytes_be(Sign::Plus, &self.to_be_bytes())
+    }
+
+    pub fn from_be_bytes(bytes: &[u8; 32]) -> U256 {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let mut b = [0u8; 8];
+            b.copy_from_slice(&bytes[32 - 8 * (i + 1)..32 - 8 * i]);
+            *limb = u64::from_be_bytes(b);
+        }
+        U256(limbs)
+    }
+
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (i, limb) in self.0.iter().enumerate() {
+            bytes[32 - 8 * (i + 1)..32 - 8 * i].copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.iter().all(|limb| *limb == 0)
+    }
+
+    /// i-th bit
+    pub fn bit(&self, i: usize) -> bool {
+        (self.0[i / 64] >> (i % 64)) & 1 == 1
+    }
+
+    /// number of significant bits
+    pub fn bits(&self) -> usize {
+        for i in (0..4).rev() {
+            if self.0[i] != 0 {
+                return 64 * i + 64 - self.0[i].leading_zeros() as usize;
+            }
+        }
+        0
+    }
+
+    /// self + other, carry
+    pub fn overflowing_add(&self, other: &U256) -> (U256, bool) {
+        let mut r = [0u64; 4];
+        let mut carry = 0u128;
+        for (i, limb) in r.iter_mut().enumerate() {
+            let v = u128::from(self.0[i]) + u128::from(other.0[i]) + carry;
+            *limb = v as u64;
+            carry = v >> 64;
+        }
+        (U256(r), carry != 0)
+    }
+
+    /// self - other, borrow
+    pub fn overflowing_sub(&self, other: &U256) -> (U256, bool) {
+        let mut r = [0u64; 4];
+        let mut borrow = false;
+        for (i, limb) in r.iter_mut().enumerate() {
+            let (v, b1) = self.0[i].overflowing_sub(other.0[i]);
+            let (v, b2) = v.overflowing_sub(borrow as u64);
+            *limb = v;
+            borrow = b1 || b2;
+        }
+        (U256(r), borrow)
+    }
+
+    /// 512-bit product
+    pub fn mul_wide(&self, other: &U256) -> [u64; 8] {
+        let mut r = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let v = u128::from(self.0[i]) * u128::from(other.0[j]) + u128::from(r[i + j]) + carry;
+                r[i + j] = v as u64;
+                carry = v >> 64;
+            }
+            r[i + 4] = carry as u64;
+        }
+        r
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &U256) -> Ordering {
+        for i in (0..4).rev() {
+            match self.0[i].cmp(&other.0[i]) {
+                Ordering::Equal => continue,
+                ord => return ord,
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &U256) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_bigint())
+    }
+}
+
+/// p = 2^256 - 2^32 - 977
+pub const SECP256K1_P: U256 = U256([
+    0xFFFF_FFFE_FFFF_FC2F,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0xFFFF_FFFF_FFFF_FFFF,
+]);
+
+/// 2^256 - p = 2^32 + 977
+const SECP256K1_C: u128 = 0x1_0000_03D1;
+
+/// Element of GF(p), p = 2^256 - 2^32 - 977, always reduced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FieldElement(U256);
+
+impl FieldElement {
+    pub const ZERO: FieldElement = FieldElement(U256::ZERO);
+    pub const ONE: FieldElement = FieldElement(U256::ONE);
+
+    pub fn new(n: &U256) -> FieldElement {
+        if n >= &SECP256K1_P {
+            FieldElement(n.overflowing_sub(&SECP256K1_P).0)
+        } else {
+            FieldElement(*n)
+        }
+    }
+
+    pub fn from_bigint(n: &BigInt) -> FieldElement {
+        FieldElement(U256::from_bigint(&n.mod_floor(&SECP256K1_P.to_bigint())))
+    }
+
+    pub fn from_u64(n: u64) -> FieldElement {
+        FieldElement(U256([n, 0, 0, 0]))
+    }
+
+    /// 32 big-endian bytes, None unless 0 <= value < p
+    pub fn from_be_bytes(bytes: &[u8; 32]) -> Option<FieldElement> {
+        let n = U256::from_be_bytes(bytes);
+        if n < SECP256K1_P {
+            Some(FieldElement(n))
+        } else {
+            None
+        }
+    }
+
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        self.0.to_be_bytes()
+    }
+
+    pub fn to_u256(&self) -> U256 {
+        self.0
+    }
+
+    pub fn to_bigint(&self) -> BigInt {
+        self.0.to_bigint()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    pub fn is_odd(&self) -> bool {
+        self.0.bit(0)
+    }
+
+    pub fn add(&self, other: &FieldElement) -> FieldElement {
+        let (s, carry) = self.0.overflowing_add(&other.0);
+        if carry || s >= SECP256K1_P {
+            FieldElement(s.overflowing_sub(&SECP256K1_P).0)
+        } else {
+            FieldElement(s)
+        }
+    }
+
+    pub fn sub(&self, other: &FieldElement) -> FieldElement {
+        let (d, borrow) = self.0.overflowing_sub(&other.0);
+        if borrow {
+            FieldElement(d.overflowing_add(&SECP256K1_P).0)
+        } else {
+            FieldElement(d)
+        }
+    }
+
+    pub fn neg(&self) -> FieldElement {
+        FieldElement::ZERO.sub(self)
+    }
+
+    pub fn mul(&self, other: &FieldElement) -> FieldElement {
+        FieldElement::reduce_wide(&self.0.mul_wide(&other.0))
+    }
+
+    pub fn square(&self) -> FieldElement {
+        self.mul(self)
+    }
+
+    /// n * self for small n
+    pub fn mul_u64(&self, n: u64) -> FieldElement {
+        self.mul(&FieldElement::from_u64(n))
+    }
+
+    /// hi 2^256 + lo = hi (2^32 + 977) + lo (mod p)
+    fn reduce_wide(t: &[u64; 8]) -> FieldElement {
+        let mut r = [0u64; 5];
+        let mut carry = 0u128;
+        for i in 0..4 {
+            let v = u128::from(t[i + 4]) * SECP256K1_C + u128::from(t[i]) + carry;
+            r[i] = v as u64;
+            carry = v >> 64;
+        }
+        r[4] = carry as u64;
+        // fold the limb above 2^256 once more
+        let mut carry = u128::from(r[4]) * SECP256K1_C;
+        let mut limbs = [0u64; 4];
+        for i in 0..4 {
+            let v = u128::from(r[i]) + carry;
+            limbs[i] = v as u64;
+            carry = v >> 64;
+        }
+        let mut n = U256(limbs);
+        if carry != 0 {
+            // the low part is small here, so this cannot overflow
+            n = n.overflowing_add(&U256([SECP256K1_C as u64, 0, 0, 0])).0;
+        }
+        FieldElement::new(&n)
+    }
+
+    /// self^e
+    pub fn pow(&self, e: &U256) -> FieldElement {
+        let mut r = FieldElement::ONE;
+        for i in (0..e.bits()).rev() {
+            r = r.square();
+            if e.bit(i) {
+                r = r.mul(self);
+            }
+        }
+        r
+    }
+
+    /// self^(p - 2)
+    pub fn inverse(&self) -> FieldElement {
+        assert!(!self.is_zero(), "inverse of zero");
+        self.pow(&SECP256K1_P.overflowing_sub(&U256([2, 0, 0, 0])).0)
+    }
+
+    /// self^((p + 1) / 4), p = 3 (mod 4)
+    pub fn sqrt(&self) -> Option<FieldElement> {
+        let (e, _) = SECP256K1_P.overflowing_add(&U256::ONE);
+        let e = U256([
+            (e.0[0] >> 2) | (e.0[1] << 62),
+            (e.0[1] >> 2) | (e.0[2] << 62),
+            (e.0[2] >> 2) | (e.0[3] << 62),
+            e.0[3] >> 2,
+        ]);
+        let r = self.pow(&e);
+        if &r.square() == self {
+            Some(r)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for FieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[test]
+fn u256_test() {
+    let n = BigInt::parse_bytes(b"79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798", 16).unwrap();
+    let a = U256::from_bigint(&n);
+    assert_eq!(a.to_bigint(), n);
+    assert_eq!(U256::from_be_bytes(&a.to_be_bytes()), a);
+    assert_eq!(a.bits(), 255);
+    assert_eq!(U256::ONE.bits(), 1);
+    assert!(U256::ZERO < U256::ONE && a > U256::ONE);
+    let wide = a.mul_wide(&a);
+    let mut bytes: Vec<u8> = Vec::new();
+    for limb in wide.iter().rev() {
+        bytes.extend_from_slice(&limb.to_be_bytes());
+    }
+    assert_eq!(BigInt::from_bytes_be(Sign::Plus, &bytes), &n * &n);
+    let (_, borrow) = U256::ZERO.overflowing_sub(&U256::ONE);
+    assert!(borrow);
+    assert_eq_str!(SECP256K1_P, "115792089237316195423570985008687907853269984665640564039457584007908834671663");
+}
+
+#[test]
+fn field_element_test() {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+    use crate::bigint::{Inverse, PowerModulo};
+    let p = SECP256K1_P.to_bigint();
+    let mut rng = StdRng::seed_from_u64(38);
+    let mut values: Vec<BigInt> = vec![BigInt::from(0), BigInt::from(1), &p - 1, &p - 2];
+    for _ in 0..20 {
+        let bytes: [u8; 32] = rng.gen();
+        values.push(BigInt::from_bytes_be(Sign::Plus, &bytes));
+    }
+    for a in &values {
+        let fa = FieldElement::from_bigint(a);
+        assert_eq!(fa.to_bigint(), a.mod_floor(&p));
+        assert_eq!(fa.neg().to_bigint(), (-a).mod_floor(&p));
+        if !fa.is_zero() {
+            assert_eq!(fa.inverse().to_bigint(), a.mod_floor(&p).inverse(&p));
+        }
+        for b in &values {
+            let fb = FieldElement::from_bigint(b);
+            assert_eq!(fa.add(&fb).to_bigint(), (a + b).mod_floor(&p));
+            assert_eq!(fa.sub(&fb).to_bigint(), (a - b).mod_floor(&p));
+            assert_eq!(fa.mul(&fb).to_bigint(), (a * b).mod_floor(&p));
+        }
+    }
+    let seven = FieldElement::from_u64(7);
+    assert_eq!(seven.pow(&U256::from_bigint(&BigInt::from(100))).to_bigint(),
+        BigInt::from(7).power_modulo(&BigInt::from(100), &p));
+    let two = FieldElement::from_u64(2);
+    let r = two.sqrt().unwrap();
+    assert_eq!(r.square(), two);
+    assert!(seven.sqrt().is_none());
+}
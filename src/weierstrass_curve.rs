@@ -0,0 +1,316 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Zero, One};
+use std::fmt;
+use crate::bigint::{Inverse, Power};
+use super::curve::Curve;
+use super::elliptic_curve::{EllipticCurve, ECPoint};
+use super::polynomial;
+use super::term_builder::TermBuildable;
+use super::term_builder;
+
+/// y^2 + a1 x y + a3 y = x^3 + a2 x^2 + a4 x + a6
+/// GF(p)
+#[derive(Debug, Clone)]
+pub struct WeierstrassCurve {
+    pub a1: BigInt,
+    pub a2: BigInt,
+    pub a3: BigInt,
+    pub a4: BigInt,
+    pub a6: BigInt,
+    pub p: BigInt,
+}
+
+impl WeierstrassCurve {
+    pub fn new(a1: &BigInt, a2: &BigInt, a3: &BigInt, a4: &BigInt, a6: &BigInt, p: &BigInt) -> WeierstrassCurve {
+        assert!(p >= &BigInt::from(2));
+        WeierstrassCurve {
+            a1: a1.mod_floor(p),
+            a2: a2.mod_floor(p),
+            a3: a3.mod_floor(p),
+            a4: a4.mod_floor(p),
+            a6: a6.mod_floor(p),
+            p: p.clone(),
+        }
+    }
+
+    /// y^2 = x^3 + a x + b as a1 = a2 = a3 = 0, a4 = a, a6 = b
+    pub fn from_short(ec: &EllipticCurve) -> WeierstrassCurve {
+        WeierstrassCurve::new(&Zero::zero(), &Zero::zero(), &Zero::zero(), &ec.a, &ec.b, &ec.p)
+    }
+
+    /// a1^2 + 4 a2
+    pub fn b2(&self) -> BigInt {
+        (self.a1.power(2) + BigInt::from(4) * &self.a2).mod_floor(&self.p)
+    }
+
+    /// 2 a4 + a1 a3
+    pub fn b4(&self) -> BigInt {
+        (BigInt::from(2) * &self.a4 + &self.a1 * &self.a3).mod_floor(&self.p)
+    }
+
+    /// a3^2 + 4 a6
+    pub fn b6(&self) -> BigInt {
+        (self.a3.power(2) + BigInt::from(4) * &self.a6).mod_floor(&self.p)
+    }
+
+    /// a1^2 a6 + 4 a2 a6 - a1 a3 a4 + a2 a3^2 - a4^2
+    pub fn b8(&self) -> BigInt {
+        let b8 = self.a1.power(2) * &self.a6
+            + BigInt::from(4) * &self.a2 * &self.a6
+            - &self.a1 * &self.a3 * &self.a4
+            + &self.a2 * self.a3.power(2)
+            - self.a4.power(2);
+        b8.mod_floor(&self.p)
+    }
+
+    /// b2^2 - 24 b4
+    pub fn c4(&self) -> BigInt {
+        (self.b2().power(2) - BigInt::from(24) * self.b4()).mod_floor(&self.p)
+    }
+
+    /// -b2^3 + 36 b2 b4 - 216 b6
+    pub fn c6(&self) -> BigInt {
+        let b2 = self.b2();
+        let c6 = -b2.power(3) + BigInt::from(36) * &b2 * self.b4() - BigInt::from(216) * self.b6();
+        c6.mod_floor(&self.p)
+    }
+
+    /// -b2^2 b8 - 8 b4^3 - 27 b6^2 + 9 b2 b4 b6
+    pub fn discriminant(&self) -> BigInt {
+        let b2 = self.b2();
+        let b4 = self.b4();
+        let b6 = self.b6();
+        let d = -b2.power(2) * self.b8()
+            - BigInt::from(8) * b4.power(3)
+            - BigInt::from(27) * b6.power(2)
+            + BigInt::from(9) * &b2 * &b4 * &b6;
+        d.mod_floor(&self.p)
+    }
+
+    pub fn is_singular(&self) -> bool {
+        self.discriminant().is_zero()
+    }
+
+    /// c4^3 / discriminant
+    pub fn j_invariant(&self) -> BigInt {
+        assert!(!self.is_singular(), "j-invariant of singular curve");
+        (self.c4().power(3) * self.discriminant().inverse(&self.p)).mod_floor(&self.p)
+    }
+
+    pub fn is_on_curve(&self, point: &ECPoint) -> bool {
+        if point.is_infinity() {
+            return true;
+        }
+        let x = &point.x;
+        let y = &point.y;
+        let lhs = y.power(2) + &self.a1 * x * y + &self.a3 * y;
+        let rhs = x.power(3) + &self.a2 * x.power(2) + &self.a4 * x + &self.a6;
+        (lhs - rhs).mod_floor(&self.p).is_zero()
+    }
+
+    /// -P = (x, -y - a1 x - a3)
+    pub fn negate(&self, point: &ECPoint) -> ECPoint {
+        if point.is_infinity() {
+            return point.clone();
+        }
+        let y = -&point.y - &self.a1 * &point.x - &self.a3;
+        ECPoint::new(&point.x, &y.mod_floor(&self.p), &point.z)
+    }
+
+    /// Elliptic curve point addition
+    pub fn plus(&self, point1: &ECPoint, point2: &ECPoint) -> ECPoint {
+        assert!(self.is_on_curve(point1), "point1 is not on curve");
+        assert!(self.is_on_curve(point2), "point2 is not on curve");
+        if point1.is_infinity() {
+            return point2.clone();
+        } else if point2.is_infinity() {
+            return point1.clone();
+        }
+        let p = &self.p;
+        let x1 = point1.x.mod_floor(p);
+        let y1 = point1.y.mod_floor(p);
+        let x2 = point2.x.mod_floor(p);
+        let y2 = point2.y.mod_floor(p);
+        let m = if x1 != x2 {
+            (&y2 - &y1) * (&x2 - &x1).inverse(p)
+        } else {
+            let d = (BigInt::from(2) * &y1 + &self.a1 * &x1 + &self.a3).mod_floor(p);
+            if y1 != y2 || d.is_zero() {
+                return ECPoint::infinity();
+            }
+            (BigInt::from(3) * x1.power(2) + BigInt::from(2) * &self.a2 * &x1 + &self.a4 - &self.a1 * &y1)
+                * d.inverse(p)
+        };
+        let m = m.mod_floor(p);
+        let n = &y1 - &m * &x1;
+        let x3 = m.power(2) + &self.a1 * &m - &self.a2 - &x1 - &x2;
+        let y3 = -(&m + &self.a1) * &x3 - n - &self.a3;
+        ECPoint::new(&x3.mod_floor(p), &y3.mod_floor(p), &One::one())
+    }
+
+    /// rational points
+    pub fn points(&self) -> Vec<ECPoint> {
+        let mut points: Vec<ECPoint> = Vec::new();
+        for x in num_iter::range(BigInt::from(0), self.p.clone()) {
+            for y in num_iter::range(BigInt::from(0), self.p.clone()) {
+                let point = ECPoint::new(&x, &y, &One::one());
+                if self.is_on_curve(&point) {
+                    points.push(point);
+                }
+            }
+        }
+        points.push(ECPoint::infinity());
+        points
+    }
+
+    /// short Weierstrass form y^2 = x^3 - 27 c4 x - 54 c6
+    pub fn to_short_weierstrass(&self) -> EllipticCurve {
+        assert!(self.p > BigInt::from(3), "characteristic 2 or 3");
+        let a = (BigInt::from(-27) * self.c4()).mod_floor(&self.p);
+        let b = (BigInt::from(-54) * self.c6()).mod_floor(&self.p);
+        EllipticCurve::new_raw(&a, &b, &self.p)
+    }
+
+    /// (x, y) -> (36 x + 3 b2, 108 (2 y + a1 x + a3))
+    pub fn map_to_short(&self, point: &ECPoint) -> ECPoint {
+        if point.is_infinity() {
+            return ECPoint::infinity();
+        }
+        let x = BigInt::from(36) * &point.x + BigInt::from(3) * self.b2();
+        let y = BigInt::from(108) * (BigInt::from(2) * &point.y + &self.a1 * &point.x + &self.a3);
+        ECPoint::new(&x.mod_floor(&self.p), &y.mod_floor(&self.p), &One::one())
+    }
+
+    /// inverse of map_to_short
+    pub fn map_from_short(&self, point: &ECPoint) -> ECPoint {
+        if point.is_infinity() {
+            return ECPoint::infinity();
+        }
+        let p = &self.p;
+        let x = ((&point.x - BigInt::from(3) * self.b2()) * BigInt::from(36).inverse(p)).mod_floor(p);
+        let y = (&point.y * BigInt::from(108).inverse(p) - &self.a1 * &x - &self.a3) * BigInt::from(2).inverse(p);
+        ECPoint::new(&x, &y.mod_floor(p), &One::one())
+    }
+}
+
+impl Curve for WeierstrassCurve {
+    type Point = ECPoint;
+
+    fn identity(&self) -> ECPoint {
+        ECPoint::infinity()
+    }
+
+    fn is_on_curve(&self, point: &ECPoint) -> bool {
+        WeierstrassCurve::is_on_curve(self, point)
+    }
+
+    fn negate(&self, point: &ECPoint) -> ECPoint {
+        WeierstrassCurve::negate(self, point)
+    }
+
+    fn plus(&self, point1: &ECPoint, point2: &ECPoint) -> ECPoint {
+        WeierstrassCurve::plus(self, point1, point2)
+    }
+
+    fn normalize(&self, point: &ECPoint) -> ECPoint {
+        if point.is_infinity() {
+            return ECPoint::infinity();
+        }
+        ECPoint::new(&point.x.mod_floor(&self.p), &point.y.mod_floor(&self.p), &One::one())
+    }
+}
+
+impl fmt::Display for WeierstrassCurve {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lhs: polynomial::Polynomial = term_builder::TermBuilder::new().ypow(2).build()
+            + term_builder::TermBuilder::new().coef(&self.a1).xpow(1).ypow(1).build()
+            + term_builder::TermBuilder::new().coef(&self.a3).ypow(1).build();
+        let rhs: polynomial::Polynomial = term_builder::TermBuilder::new().xpow(3).build()
+            + term_builder::TermBuilder::new().coef(&self.a2).xpow(2).build()
+            + term_builder::TermBuilder::new().coef(&self.a4).xpow(1).build()
+            + term_builder::TermBuilder::new().coef(&self.a6).build();
+        write!(f, "F_{}: {} = {}", self.p, lhs, rhs)
+    }
+}
+
+#[cfg(test)]
+fn check_group_law(curve: &WeierstrassCurve) {
+    let points = curve.points();
+    let n = BigInt::from(points.len());
+    for point1 in &points {
+        assert!(curve.multiply_scalar(point1, &n).is_infinity());
+        assert!(curve.plus(point1, &curve.negate(point1)).is_infinity());
+        for point2 in &points {
+            assert_eq!(curve.plus(point1, point2), curve.plus(point2, point1));
+            for point3 in &points {
+                let r1 = curve.plus(&curve.plus(point1, point2), point3);
+                let r2 = curve.plus(point1, &curve.plus(point2, point3));
+                assert_eq!(r1, r2);
+            }
+        }
+    }
+}
+
+#[test]
+fn weierstrass_curve_char2_test() {
+    // y^2 + x y = x^3 + x^2 + 1
+    let curve = WeierstrassCurve::new(&1.into(), &1.into(), &0.into(), &0.into(), &1.into(), &2.into());
+    assert_eq_str!(curve, "F_2: x y + y^2 = x^3 + x^2 + 1");
+    assert!(!curve.is_singular());
+    assert_eq_str!(curve.j_invariant(), "1");
+    assert_eq!(curve.points().len(), 2);
+    check_group_law(&curve);
+
+    // y^2 + y = x^3 over F_2 is supersingular, j = 0
+    let curve = WeierstrassCurve::new(&0.into(), &0.into(), &1.into(), &0.into(), &0.into(), &2.into());
+    assert_eq_str!(curve.j_invariant(), "0");
+    assert_eq!(curve.points().len(), 3);
+    check_group_law(&curve);
+
+    // y^2 = x^3 + x is singular in characteristic 2
+    let curve = WeierstrassCurve::new(&0.into(), &0.into(), &0.into(), &1.into(), &0.into(), &2.into());
+    assert!(curve.is_singular());
+}
+
+#[test]
+fn weierstrass_curve_char3_test() {
+    // y^2 = x^3 + x^2 + 1
+    let curve = WeierstrassCurve::new(&0.into(), &1.into(), &0.into(), &0.into(), &1.into(), &3.into());
+    assert!(!curve.is_singular());
+    assert_eq!(curve.points().len(), 6);
+    check_group_law(&curve);
+
+    // y^2 = x^3 + 1 = (x + 1)^3 has a cusp at (-1, 0)
+    let curve = WeierstrassCurve::new(&0.into(), &0.into(), &0.into(), &0.into(), &1.into(), &3.into());
+    assert!(curve.is_singular());
+    assert!(curve.discriminant().is_zero());
+}
+
+#[test]
+fn weierstrass_curve_short_test() {
+    let p = BigInt::from(13);
+    let curve = WeierstrassCurve::new(&1.into(), &2.into(), &3.into(), &4.into(), &5.into(), &p);
+    assert_eq_str!(curve, "F_13: x y + y^2 + 3 y = x^3 + 2 x^2 + 4 x + 5");
+    check_group_law(&curve);
+
+    let short = curve.to_short_weierstrass();
+    assert_eq!(curve.j_invariant(), short.j_invariant());
+    let ec = EllipticCurve::new(&short.a, &short.b, &p);
+    let points = curve.points();
+    assert_eq!(points.len(), ec.cardinality());
+    for point1 in &points {
+        let image1 = curve.map_to_short(point1);
+        assert!(short.is_on_curve(&image1));
+        assert_eq!(&curve.map_from_short(&image1), point1);
+        for point2 in &points {
+            let image2 = curve.map_to_short(point2);
+            assert_eq!(curve.map_to_short(&curve.plus(point1, point2)), short.plus(&image1, &image2));
+        }
+    }
+
+    let ec = EllipticCurve::new(&BigInt::from(1), &BigInt::from(1), &BigInt::from(5));
+    let curve = WeierstrassCurve::from_short(&ec);
+    assert_eq!(curve.j_invariant(), ec.j_invariant());
+    assert_eq!(curve.points().len(), ec.cardinality());
+}